@@ -1,26 +1,115 @@
-use clap::Args;
+use crate::money::Money;
+use clap::{Args, Subcommand};
+use std::path::PathBuf;
 
 /// Convert from one currency to another
 #[derive(Debug, Args, Clone)]
+#[command(subcommand_negates_reqs = true, args_conflicts_with_subcommands = true)]
 pub struct Currency {
-    #[clap(short, long, help = "Currency to convert from")]
+    #[command(subcommand)]
+    /// Alternative currency operations. Converts the amount when omitted
+    pub operation: Option<CurrencyOperation>,
+    #[clap(short, long, required = true, help = "Currency to convert from")]
     /// Currency to convert from
-    pub from: String,
+    pub from: Option<String>,
     #[clap(
         short,
         long,
+        required = true,
         allow_negative_numbers = false,
         help = "Amount to convert"
     )]
     /// Amount to convert
-    pub amt: f64,
-    #[clap(short, long, help = "Currency to convert to")]
-    /// Currency to convert to
+    pub amt: Option<Money>,
+    #[clap(
+        short,
+        long,
+        help = "Currency to convert to. Can be specified multiple times"
+    )]
+    /// Currencies to convert to
     pub to: Vec<String>,
+    #[clap(
+        long,
+        help = "Convert at the rate on a past date (dd/mm/yyyy or yyyymmdd) instead of the latest"
+    )]
+    /// Convert at the rate on a past date
+    pub on: Option<String>,
     #[arg(
         long,
         global = true,
         help = "API endpoint URL (e.g., https://api.example.com). Falls back to LIFESTUFF_API_ENDPOINT env var, then http://localhost:8787"
     )]
     pub endpoint: Option<String>,
+    #[arg(
+        long,
+        default_value_t = 12,
+        help = "Hours a cached rate is used for before it is fetched again. Older rates are only used when the API can't be reached"
+    )]
+    /// Hours a cached rate is used for before it is fetched again
+    pub ttl: u64,
+    #[arg(
+        long,
+        value_name = "FILE",
+        help = "Rate cache file. Falls back to currency_rates.json in LIFESTUFF_CACHE_DIR, then in $XDG_CACHE_HOME/lifestuff or ~/.cache/lifestuff"
+    )]
+    /// Rate cache file
+    pub cache: Option<PathBuf>,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum CurrencyOperation {
+    /// Show cached exchange rates and how old they are, or fetch them ahead of time with --refresh
+    Rates(Rates),
+    /// Show how the rate between two currencies moved over a range of dates
+    History(History),
+}
+
+#[derive(Debug, Args, Clone)]
+pub struct Rates {
+    #[clap(
+        value_name = "PAIR",
+        value_delimiter = ',',
+        help = "Currency pairs as FROM/TO, e.g. GBP/EUR USD/JPY. Defaults to every cached pair"
+    )]
+    /// Currency pairs as FROM/TO
+    pub pairs: Vec<String>,
+    #[clap(long, help = "Fetch the latest rates for the pairs and cache them")]
+    /// Fetch the latest rates for the pairs and cache them
+    pub refresh: bool,
+    #[arg(
+        long,
+        default_value_t = 12,
+        help = "Hours after which a cached rate is marked as stale"
+    )]
+    /// Hours after which a cached rate is marked as stale
+    pub ttl: u64,
+    #[arg(
+        long,
+        value_name = "FILE",
+        help = "Rate cache file. Falls back to currency_rates.json in LIFESTUFF_CACHE_DIR, then in $XDG_CACHE_HOME/lifestuff or ~/.cache/lifestuff"
+    )]
+    /// Rate cache file
+    pub cache: Option<PathBuf>,
+}
+
+#[derive(Debug, Args, Clone)]
+pub struct History {
+    #[clap(help = "Currency to convert from")]
+    /// Currency to convert from
+    pub source: String,
+    #[clap(help = "Currency to convert to")]
+    /// Currency to convert to
+    pub target: String,
+    #[clap(
+        long,
+        help = "First date of the range (dd/mm/yyyy or yyyymmdd). Defaults to 30 days before --to"
+    )]
+    /// First date of the range
+    pub from: Option<String>,
+    #[clap(
+        long,
+        help = "Last date of the range (dd/mm/yyyy or yyyymmdd). Defaults to today"
+    )]
+    /// Last date of the range
+    pub to: Option<String>,
 }
@@ -1,6 +1,10 @@
 use clap::{Args, Subcommand};
 pub mod add;
+pub mod age;
+pub mod cal;
 pub mod diff;
+pub mod hours;
+pub mod range;
 
 #[derive(Args, Debug)]
 pub struct DateOperations {
@@ -13,8 +17,16 @@ pub struct DateOperations {
 pub enum DateOption {
     /// Add a time period to a given date
     Add(add::Add),
+    /// Exact age, next birthday and days until it
+    Age(age::Age),
+    /// Display a calendar with bank holidays and marked dates highlighted
+    Cal(cal::Cal),
     /// Diff Two Dates
     Diff(diff::Diff),
+    /// Sum worked hours from clock-in/clock-out pairs
+    Hours(hours::Hours),
     /// Information about the ordinal date
     Ordinal,
+    /// List or count the dates in a range, with optional filters
+    Range(range::Range),
 }
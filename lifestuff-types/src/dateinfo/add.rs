@@ -2,7 +2,10 @@ use clap::{Args, ValueEnum};
 
 #[derive(Debug, Args, Clone)]
 pub struct Add {
-    #[clap(help = "Date to add time period to", long)]
+    #[clap(
+        help = "Date to add time period to (dd/mm/yyyy [HH:MM[:SS]] or yyyymmdd[THHMM[SS]])",
+        long
+    )]
     /// Date to add time period to
     pub date: Option<String>,
     /// Amount of time period to add to date
@@ -0,0 +1,11 @@
+use clap::Args;
+
+#[derive(Debug, Args, Clone)]
+pub struct Age {
+    #[clap(help = "Date of birth (dd/mm/yyyy or yyyymmdd)")]
+    /// Date of birth
+    pub birthdate: String,
+    #[clap(long, help = "Date to calculate the age on. Defaults to today")]
+    /// Date to calculate the age on. Defaults to today
+    pub on: Option<String>,
+}
@@ -0,0 +1,23 @@
+use clap::Args;
+
+#[derive(Debug, Args, Clone)]
+pub struct Cal {
+    #[clap(help = "Month to display (1-12). Defaults to the current month")]
+    /// Month to display (1-12). Defaults to the current month
+    pub month: Option<u8>,
+    #[clap(help = "Year to display. Defaults to the current year")]
+    /// Year to display. Defaults to the current year
+    pub year: Option<i32>,
+    #[clap(
+        long,
+        help = "Date to highlight (dd/mm/yyyy or yyyymmdd). Can be specified multiple times"
+    )]
+    /// Dates to highlight on the calendar
+    pub mark: Vec<String>,
+    #[clap(short = 'y', long, help = "Display every month of the year")]
+    /// Display every month of the year
+    pub full_year: bool,
+    #[clap(long, help = "Start weeks on Sunday instead of Monday")]
+    /// Start weeks on Sunday instead of Monday
+    pub sunday_start: bool,
+}
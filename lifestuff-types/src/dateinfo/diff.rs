@@ -3,7 +3,9 @@ use strum::Display;
 
 #[derive(Debug, Args, Clone)]
 pub struct Diff {
-    #[clap(help = "Date to perform diff operations on")]
+    #[clap(
+        help = "Date to perform diff operations on (dd/mm/yyyy [HH:MM[:SS]] or yyyymmdd[THHMM[SS]])"
+    )]
     /// Date to perform diff operations on
     pub date1: String,
     #[clap(help = "Optional date to diff with. Defaults to current date.")]
@@ -23,5 +25,9 @@ pub enum DateDuration {
     #[clap(action=clap::ArgAction::SetTrue)]
     Hours,
     #[clap(action=clap::ArgAction::SetTrue)]
+    Minutes,
+    #[clap(action=clap::ArgAction::SetTrue)]
+    Seconds,
+    #[clap(action=clap::ArgAction::SetTrue)]
     Years,
 }
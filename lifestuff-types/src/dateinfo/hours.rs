@@ -0,0 +1,30 @@
+use clap::Args;
+use std::path::PathBuf;
+
+#[derive(Debug, Args, Clone)]
+pub struct Hours {
+    #[clap(help = "Alternating clock-in and clock-out times (dd/mm/yyyy HH:MM or yyyymmddTHHMM)")]
+    /// Alternating clock-in and clock-out times
+    pub times: Vec<String>,
+    #[clap(
+        short,
+        long,
+        help = "CSV file of `clock_in,clock_out[,break_minutes]` rows"
+    )]
+    /// CSV file of `clock_in,clock_out[,break_minutes]` rows
+    pub file: Option<PathBuf>,
+    #[clap(
+        short,
+        long = "break",
+        default_value_t = 0,
+        help = "Unpaid break in minutes subtracted from each shift without its own break"
+    )]
+    /// Unpaid break in minutes subtracted from each shift without its own break
+    pub break_minutes: u32,
+    #[clap(long, help = "Hours per day after which time counts as overtime")]
+    /// Hours per day after which time counts as overtime
+    pub daily_overtime: Option<f64>,
+    #[clap(long, help = "Hours per ISO week after which time counts as overtime")]
+    /// Hours per ISO week after which time counts as overtime
+    pub weekly_overtime: Option<f64>,
+}
@@ -0,0 +1,88 @@
+use clap::{Args, ValueEnum};
+use std::str::FromStr;
+
+#[derive(Debug, Args, Clone)]
+pub struct Range {
+    #[clap(help = "Start of the range, inclusive (dd/mm/yyyy, yyyymmdd or `today`)")]
+    /// Start of the range, inclusive
+    pub start: String,
+    #[clap(help = "End of the range, inclusive (dd/mm/yyyy, yyyymmdd or `today`)")]
+    /// End of the range, inclusive
+    pub end: String,
+    #[clap(
+        long,
+        value_enum,
+        help = "Only include weekdays, weekends or business days"
+    )]
+    /// Only include weekdays, weekends or business days
+    pub kind: Option<DayKind>,
+    #[clap(
+        long,
+        value_enum,
+        help = "Only include the given day of the week. Can be specified multiple times"
+    )]
+    /// Only include the given days of the week
+    pub day: Vec<RangeWeekday>,
+    #[clap(
+        long,
+        help = "Only include the given day of the month (1-31 or `last`). Can be specified multiple times"
+    )]
+    /// Only include the given days of the month
+    pub dom: Vec<DayOfMonth>,
+    #[clap(short, long, help = "Only print the number of matching dates")]
+    /// Only print the number of matching dates
+    pub count: bool,
+}
+
+#[derive(Debug, ValueEnum, Clone, Copy, PartialEq, Eq)]
+pub enum DayKind {
+    /// Monday to Friday
+    Weekday,
+    /// Saturday and Sunday
+    Weekend,
+    /// Monday to Friday, excluding England and Wales bank holidays
+    #[clap(aliases = ["business", "working-day"])]
+    BusinessDay,
+}
+
+//noinspection SpellCheckingInspection
+#[derive(Debug, ValueEnum, Clone, Copy, PartialEq, Eq)]
+pub enum RangeWeekday {
+    #[clap(aliases = ["mon"])]
+    Monday,
+    #[clap(aliases = ["tue", "tues"])]
+    Tuesday,
+    #[clap(aliases = ["wed"])]
+    Wednesday,
+    #[clap(aliases = ["thu", "thur", "thurs"])]
+    Thursday,
+    #[clap(aliases = ["fri"])]
+    Friday,
+    #[clap(aliases = ["sat"])]
+    Saturday,
+    #[clap(aliases = ["sun"])]
+    Sunday,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DayOfMonth {
+    Day(u8),
+    Last,
+}
+
+impl FromStr for DayOfMonth {
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        if input.eq_ignore_ascii_case("last") {
+            return Ok(Self::Last);
+        }
+
+        match input.parse::<u8>() {
+            Ok(day) if (1..=31).contains(&day) => Ok(Self::Day(day)),
+            _ => Err(format!(
+                "Invalid day of month '{input}'. Must be between 1 and 31 or `last`"
+            )),
+        }
+    }
+}
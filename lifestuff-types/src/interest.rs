@@ -1,39 +1,85 @@
-use clap::Args;
+use crate::money::{Money, RoundingMode};
+use clap::{Args, Subcommand, ValueEnum};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::PathBuf;
+use std::str::FromStr;
 
-#[derive(Debug, Args, Clone)]
+pub mod compare;
+pub mod debts;
+pub mod montecarlo;
+pub mod offers;
+pub mod savings;
+pub mod solve;
+
+/// Options for interest calculations. These can also be saved as a TOML or JSON scenario file,
+/// using the field names as keys.
+#[derive(Debug, Args, Clone, Default, Serialize, Deserialize)]
+#[command(subcommand_negates_reqs = true)]
+#[serde(default, deny_unknown_fields)]
 pub struct Interest {
+    #[command(subcommand)]
+    #[serde(skip)]
+    /// Alternative operations on the loan. Prints the amortization schedule when omitted
+    pub operation: Option<InterestOperation>,
+    #[clap(
+        help = "TOML or JSON scenario file of interest options. Options passed on the command line take precedence",
+        long
+    )]
+    #[serde(skip)]
+    /// Scenario file of interest options
+    pub scenario: Option<PathBuf>,
+    #[clap(
+        help = "Save the options, including any from --scenario, as a TOML or JSON scenario file (by extension)",
+        long,
+        value_name = "FILE"
+    )]
+    #[serde(skip)]
+    /// File to save the options to as a scenario
+    pub save_scenario: Option<PathBuf>,
     #[clap(
-        help = "Principal left on mortgage",
+        help = "Principal left on mortgage. Taken from the mortgage API with --from-mortgage",
         short,
         long,
         allow_negative_numbers = false,
-        required = true
+        required_unless_present_any = ["from_mortgage", "scenario"]
     )]
     /// Principal left on mortgage
-    pub principal: f32,
+    pub principal: Option<Money>,
     #[clap(
-        help = "Interest rate (%)",
+        help = "Interest rate (%). Taken from the mortgage API with --from-mortgage",
         short,
         long,
         allow_negative_numbers = false,
-        required = true
+        required_unless_present_any = ["from_mortgage", "scenario"]
     )]
     /// Current Interest rate (%)
-    pub interest_rate: f32,
+    pub interest_rate: Option<f64>,
     #[clap(
-        help = "Monthly payment amount",
+        help = "Interest rate change as dd/mm/yyyy=rate (%), e.g. at the end of a fixed period. Can be specified multiple times",
+        long = "rate-change",
+        value_name = "DATE=RATE"
+    )]
+    /// Interest rate changes, applied from the first payment date on or after each date
+    pub rate_changes: Vec<RateChange>,
+    #[clap(
+        help = "File of interest rate changes, one `dd/mm/yyyy,rate` per line",
+        long
+    )]
+    /// File of interest rate changes, one `dd/mm/yyyy,rate` per line
+    pub rate_schedule: Option<PathBuf>,
+    #[clap(
+        help = "Monthly payment amount. Required unless solving for it or using --from-mortgage",
         long,
-        allow_negative_numbers = false,
-        required = true
+        allow_negative_numbers = false
     )]
     /// Monthly payment amount
-    pub repayment: f32,
+    pub repayment: Option<Money>,
     #[clap(
-        help = "Max annual repayment percentage (%)",
+        help = "Max annual repayment percentage (%). With --annual-downpayment, the allowance it is charged against instead of 10%",
         short,
         long,
         allow_negative_numbers = false,
-        required = true,
         visible_alias = "annual-limit"
     )]
     /// Maximum annual repayment percentage (%) allowed
@@ -41,20 +87,298 @@ pub struct Interest {
     #[clap(
         help = "Max annual supplementary downpayment ",
         short,
-        conflicts_with = "max_repayment_pct",
         long,
-        required = true,
         allow_negative_numbers = false,
         visible_alias = "annual-downpayment"
     )]
     /// Maximum annual supplementary downpayment allowed
-    pub annual_downpayment: Option<f32>,
+    pub annual_downpayment: Option<Money>,
+    #[clap(
+        help = "Completion anniversary (dd/mm) on which the annual overpayment allowance resets. Defaults to 1 January",
+        long,
+        value_name = "DD/MM"
+    )]
+    /// Completion anniversary on which the annual overpayment allowance resets
+    pub anniversary: Option<Anniversary>,
+    #[clap(
+        help = "Early repayment charge (%) on overpayments above the allowance for each product year, starting with the current one, e.g. 5,4,3",
+        long = "erc",
+        value_delimiter = ',',
+        value_name = "PCT",
+        allow_negative_numbers = false
+    )]
+    /// Early repayment charges (%) for each product year, starting with the current one
+    #[serde(alias = "erc")]
+    pub early_repayment_charges: Vec<f64>,
+    #[clap(
+        help = "Savings balance offset against the mortgage, reducing the balance interest is charged on",
+        long,
+        allow_negative_numbers = false
+    )]
+    /// Savings balance offset against the mortgage
+    pub offset: Option<Money>,
+    #[clap(
+        help = "File of offset savings balances, one `dd/mm/yyyy,balance` per line, each applying from its date",
+        long,
+        conflicts_with = "offset"
+    )]
+    /// File of offset savings balances, one `dd/mm/yyyy,balance` per line
+    pub offset_schedule: Option<PathBuf>,
+    #[clap(
+        help = "Interest rate (%) the offset savings would earn if held separately",
+        long,
+        allow_negative_numbers = false,
+        default_value_t = 0.0
+    )]
+    /// Interest rate (%) the offset savings would earn if held separately
+    pub savings_rate: f64,
+    #[clap(
+        help = "Annual inflation (%) used to also show amounts in today's money",
+        long,
+        allow_negative_numbers = true
+    )]
+    /// Flat annual inflation (%) for real terms figures
+    pub inflation: Option<f64>,
+    #[clap(
+        help = "CPI series file, one `dd/mm/yyyy,index` per line, used to also show amounts in today's money",
+        long,
+        conflicts_with = "inflation"
+    )]
+    /// CPI series file, one `dd/mm/yyyy,index` per line, for real terms figures
+    pub cpi: Option<PathBuf>,
     #[clap(
-        help = "Mortgage calculation end date (dd/mm/yyyy)",
+        help = "Mortgage calculation end date (dd/mm/yyyy). Runs until the balance is cleared when omitted",
         short,
         long,
         allow_negative_numbers = false
     )]
-    /// Mortgage calculation end date (dd/mm/yyyy)
-    pub end_date: String,
+    /// Mortgage calculation end date (dd/mm/yyyy). Runs until the balance is cleared when omitted
+    pub end_date: Option<String>,
+    #[clap(
+        help = "Date the mortgage term ends (dd/mm/yyyy). When given, the monthly payment is recalculated on each rate change to clear the balance by then. Otherwise --repayment is kept",
+        long,
+        value_name = "DATE"
+    )]
+    /// Date the mortgage term ends (dd/mm/yyyy)
+    pub term: Option<String>,
+    #[clap(
+        help = "Output format for the amortization schedule",
+        long,
+        value_enum,
+        default_value_t = OutputFormat::Table
+    )]
+    /// Output format for the amortization schedule
+    pub format: OutputFormat,
+    #[clap(
+        help = "How amounts are rounded to the penny",
+        long,
+        value_enum,
+        default_value_t = RoundingMode::HalfEven
+    )]
+    /// How amounts are rounded to the penny
+    pub rounding: RoundingMode,
+    #[clap(
+        help = "When accrued interest is added to the balance",
+        long,
+        value_enum,
+        default_value_t = Compounding::Daily
+    )]
+    /// When accrued interest is added to the balance
+    pub convention: Compounding,
+    #[clap(
+        help = "Day-count convention for the daily interest rate",
+        long,
+        value_enum,
+        default_value_t = DayCount::ActualActual
+    )]
+    /// Day-count convention for the daily interest rate
+    pub day_count: DayCount,
+    #[clap(
+        help = "Take the balance, rate and monthly payment from the mortgage API and simulate forward from the last payment. Any of them passed explicitly take precedence",
+        long
+    )]
+    /// Seed the simulation from the mortgage API's current status
+    pub from_mortgage: bool,
+    #[clap(
+        help = "Mortgage API endpoint URL used with --from-mortgage. Falls back to LIFESTUFF_API_ENDPOINT env var, then http://localhost:8787",
+        long,
+        requires = "from_mortgage"
+    )]
+    /// Mortgage API endpoint URL used with --from-mortgage
+    pub endpoint: Option<String>,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum InterestOperation {
+    /// Compare overpayment strategies side by side
+    Compare(compare::Compare),
+    /// Plan paying off several debts from one monthly budget, comparing snowball, avalanche and custom orders
+    Debts(debts::Debts),
+    /// Simulate the loan across randomly generated interest rate paths
+    #[command(name = "montecarlo", alias = "monte-carlo")]
+    MonteCarlo(montecarlo::MonteCarlo),
+    /// Compare the true cost of remortgage offers, including fees, over their fixed periods
+    Offers(offers::Offers),
+    /// Project a savings pot, using the principal as the opening balance and the interest rate as the savings rate
+    Savings(savings::Savings),
+    /// Find the payment needed to clear the balance by the end date
+    Solve(solve::Solve),
+}
+
+/// When accrued interest is added to the balance, as used by lenders
+#[derive(Debug, ValueEnum, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Compounding {
+    /// Interest is added to the balance every day
+    #[default]
+    Daily,
+    /// Interest accrues on the balance after each monthly payment and is added at the end of the month
+    #[clap(aliases = ["monthly-rest", "opening-balance"])]
+    #[serde(alias = "monthly-rest", alias = "opening-balance")]
+    Monthly,
+    /// Interest accrues on the balance at the start of each year and is added at the end of every month
+    #[clap(aliases = ["annual-rest"])]
+    #[serde(alias = "annual-rest")]
+    Annual,
+}
+
+/// How the annual rate is divided into a daily rate
+#[derive(Debug, ValueEnum, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DayCount {
+    /// Actual days over 365, or 366 in leap years
+    #[default]
+    #[clap(name = "act/act", aliases = ["actual-actual"])]
+    #[serde(rename = "act/act", alias = "actual-actual")]
+    ActualActual,
+    /// Actual days over 365, even in leap years
+    #[clap(name = "act/365", aliases = ["actual-365"])]
+    #[serde(rename = "act/365", alias = "actual-365")]
+    Actual365,
+    /// Actual days over 360
+    #[clap(name = "act/360", aliases = ["actual-360"])]
+    #[serde(rename = "act/360", alias = "actual-360")]
+    Actual360,
+    /// Every month counts as 30 days of a 360 day year
+    #[clap(name = "30/360", aliases = ["30-360"])]
+    #[serde(rename = "30/360", alias = "30-360")]
+    Thirty360,
+}
+
+#[derive(Debug, ValueEnum, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    /// Human readable table with a summary line
+    #[default]
+    Table,
+    /// Comma separated values with a header row
+    Csv,
+    /// JSON document containing every row and the totals
+    Json,
+}
+
+/// A new interest rate (%) taking effect on a date, given as `dd/mm/yyyy=rate`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct RateChange {
+    pub date: String,
+    pub rate: f64,
+}
+
+impl FromStr for RateChange {
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let (date, rate) = input
+            .split_once(['=', ','])
+            .ok_or_else(|| format!("Invalid rate change '{input}'. Expected dd/mm/yyyy=rate"))?;
+
+        match rate.trim().trim_end_matches('%').parse::<f64>() {
+            Ok(rate) if rate.is_finite() && rate >= 0.0 => Ok(Self {
+                date: date.trim().to_string(),
+                rate,
+            }),
+            _ => Err(format!(
+                "Invalid rate '{rate}' in rate change '{input}'. Must be a non-negative percentage"
+            )),
+        }
+    }
+}
+
+impl fmt::Display for RateChange {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "{}={}", self.date, self.rate)
+    }
+}
+
+impl TryFrom<String> for RateChange {
+    type Error = String;
+
+    fn try_from(input: String) -> Result<Self, Self::Error> {
+        input.parse()
+    }
+}
+
+impl From<RateChange> for String {
+    fn from(rate_change: RateChange) -> Self {
+        rate_change.to_string()
+    }
+}
+
+/// Day and month a mortgage completed on, given as `dd/mm`. Lenders reset the annual
+/// overpayment allowance on this date each year.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Anniversary {
+    pub day: u8,
+    pub month: u8,
+}
+
+/// 1 January, for lenders that reset the allowance at the start of each calendar year
+impl Default for Anniversary {
+    fn default() -> Self {
+        Self { day: 1, month: 1 }
+    }
+}
+
+impl FromStr for Anniversary {
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("Invalid anniversary '{input}'. Expected dd/mm");
+        let (day, month) = input.trim().split_once('/').ok_or_else(invalid)?;
+        let day = day.trim().parse::<u8>().map_err(|_| invalid())?;
+        let month = month.trim().parse::<u8>().map_err(|_| invalid())?;
+
+        let days_in_month = match month {
+            2 => 29,
+            4 | 6 | 9 | 11 => 30,
+            1..=12 => 31,
+            _ => return Err(invalid()),
+        };
+        if day == 0 || day > days_in_month {
+            return Err(invalid());
+        }
+
+        Ok(Self { day, month })
+    }
+}
+
+impl fmt::Display for Anniversary {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "{:02}/{:02}", self.day, self.month)
+    }
+}
+
+impl TryFrom<String> for Anniversary {
+    type Error = String;
+
+    fn try_from(input: String) -> Result<Self, Self::Error> {
+        input.parse()
+    }
+}
+
+impl From<Anniversary> for String {
+    fn from(anniversary: Anniversary) -> Self {
+        anniversary.to_string()
+    }
 }
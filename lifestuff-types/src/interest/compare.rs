@@ -0,0 +1,27 @@
+use crate::money::Money;
+use clap::Args;
+
+#[derive(Debug, Args, Clone)]
+pub struct Compare {
+    #[clap(
+        long,
+        allow_negative_numbers = false,
+        help = "Monthly overpayment to compare. Can be specified multiple times"
+    )]
+    /// Monthly overpayments to compare
+    pub monthly_overpayment: Vec<Money>,
+    #[clap(
+        long,
+        allow_negative_numbers = false,
+        help = "Max annual repayment percentage (%) to compare. Defaults to the loan's --max-repayment-pct"
+    )]
+    /// Maximum annual repayment percentage (%) to compare
+    pub max_repayment_pct: Option<u8>,
+    #[clap(
+        long,
+        allow_negative_numbers = false,
+        help = "Annual downpayment to compare. Defaults to the loan's --annual-downpayment"
+    )]
+    /// Annual supplementary downpayment to compare
+    pub annual_downpayment: Option<Money>,
+}
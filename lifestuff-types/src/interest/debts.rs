@@ -0,0 +1,27 @@
+use crate::money::Money;
+use clap::Args;
+use std::path::PathBuf;
+
+#[derive(Debug, Args, Clone)]
+pub struct Debts {
+    #[clap(
+        help = "TOML or CSV file of debts, each with a name, balance, rate (%) and minimum monthly payment"
+    )]
+    /// TOML or CSV file of debts to plan repayments for
+    pub file: PathBuf,
+    #[clap(
+        long,
+        allow_negative_numbers = false,
+        help = "Total paid towards all debts each month. Anything above the minimum payments goes to the debt being targeted"
+    )]
+    /// Total paid towards all debts each month
+    pub budget: Money,
+    #[clap(
+        long,
+        value_name = "NAME",
+        value_delimiter = ',',
+        help = "Order to target debts in for the custom plan, by name, e.g. \"Car finance,Credit card\". Unnamed debts follow in file order. Defaults to the order in the file"
+    )]
+    /// Order to target debts in for the custom plan
+    pub order: Vec<String>,
+}
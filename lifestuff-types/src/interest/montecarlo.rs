@@ -0,0 +1,43 @@
+use clap::Args;
+
+#[derive(Debug, Args, Clone)]
+pub struct MonteCarlo {
+    #[clap(
+        long,
+        default_value_t = 1000,
+        help = "Number of rate paths to simulate"
+    )]
+    /// Number of rate paths to simulate
+    pub paths: usize,
+    #[clap(
+        long,
+        default_value_t = 0,
+        help = "Seed for the random rate paths. The same seed always gives the same results"
+    )]
+    /// Seed for the random rate paths
+    pub seed: u64,
+    #[clap(
+        long,
+        default_value_t = 1.0,
+        allow_negative_numbers = false,
+        help = "Standard deviation of the rate change over a year, in percentage points"
+    )]
+    /// Standard deviation of the rate change over a year, in percentage points
+    pub volatility: f64,
+    #[clap(
+        long = "remortgage",
+        value_name = "DATE",
+        help = "Remortgage date (dd/mm/yyyy) on which the rate changes. Can be specified multiple times. The rate changes every year when omitted"
+    )]
+    /// Dates the rate changes on, instead of every year
+    pub remortgage_dates: Vec<String>,
+    #[clap(
+        long = "shock",
+        value_name = "PP",
+        value_delimiter = ',',
+        allow_negative_numbers = true,
+        help = "Possible rate changes in percentage points, one picked at random at each rate change, e.g. -1,0,1,2. A random walk using --volatility when omitted"
+    )]
+    /// Possible rate changes in percentage points, one picked at random at each rate change
+    pub shocks: Vec<f64>,
+}
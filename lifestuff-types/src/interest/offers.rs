@@ -0,0 +1,11 @@
+use clap::Args;
+use std::path::PathBuf;
+
+#[derive(Debug, Args, Clone)]
+pub struct Offers {
+    #[clap(
+        help = "TOML or CSV file of offers, each with a name, rate, term (years) and optional product_fee, valuation_fee and cashback"
+    )]
+    /// TOML or CSV file of offers to compare
+    pub file: PathBuf,
+}
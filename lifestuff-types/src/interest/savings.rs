@@ -0,0 +1,60 @@
+use crate::money::Money;
+use clap::{Args, ValueEnum};
+
+#[derive(Debug, Args, Clone)]
+pub struct Savings {
+    #[clap(
+        long,
+        allow_negative_numbers = false,
+        default_value_t = Money::ZERO,
+        help = "Contribution paid in on the first of each month"
+    )]
+    /// Contribution paid in on the first of each month
+    pub contribution: Money,
+    #[clap(
+        long,
+        value_enum,
+        default_value_t = SavingsCompounding::Monthly,
+        help = "How often interest is credited to the pot"
+    )]
+    /// How often interest is credited to the pot
+    pub compounding: SavingsCompounding,
+    #[clap(
+        long,
+        allow_negative_numbers = false,
+        help = "Cap on contributions per tax year (6 April to 5 April)"
+    )]
+    /// Cap on contributions per tax year
+    pub annual_cap: Option<Money>,
+    #[clap(
+        long,
+        conflicts_with_all = ["annual_cap", "tax_rate"],
+        help = "Treat the pot as an ISA: contributions capped at 20000 per tax year and interest tax free"
+    )]
+    /// Treat the pot as an ISA
+    pub isa: bool,
+    #[clap(
+        long,
+        allow_negative_numbers = false,
+        help = "Tax rate (%) on interest above the personal savings allowance"
+    )]
+    /// Tax rate (%) on interest above the personal savings allowance
+    pub tax_rate: Option<f64>,
+    #[clap(
+        long,
+        allow_negative_numbers = false,
+        default_value_t = Money::from_major(1000),
+        help = "Personal savings allowance of tax free interest per tax year"
+    )]
+    /// Personal savings allowance of tax free interest per tax year
+    pub allowance: Money,
+}
+
+#[derive(Debug, ValueEnum, Clone, Copy, PartialEq, Eq)]
+pub enum SavingsCompounding {
+    Daily,
+    Monthly,
+    Quarterly,
+    #[clap(aliases = ["annual", "yearly"])]
+    Annually,
+}
@@ -0,0 +1,22 @@
+use clap::{Args, ValueEnum};
+
+#[derive(Debug, Args, Clone)]
+pub struct Solve {
+    #[clap(
+        long = "for",
+        value_enum,
+        default_value_t = SolveFor::Payment,
+        help = "Amount to solve for"
+    )]
+    /// Amount to solve for
+    pub solve_for: SolveFor,
+}
+
+#[derive(Debug, ValueEnum, Clone, Copy, PartialEq, Eq)]
+pub enum SolveFor {
+    /// Monthly payment, keeping any annual overpayment fixed
+    Payment,
+    /// Annual overpayment made on the completion anniversary (--anniversary), keeping --repayment fixed
+    #[clap(aliases = ["annual", "overpayment"])]
+    AnnualOverpayment,
+}
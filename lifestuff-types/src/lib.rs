@@ -7,6 +7,7 @@ pub mod dateinfo;
 pub mod ddg;
 pub mod interest;
 pub mod mileage;
+pub mod money;
 pub mod mortgage;
 
 pub fn parse() -> Cli {
@@ -36,7 +37,7 @@ pub enum Commands {
     /// Date Operations
     Dates(dateinfo::DateOperations),
     /// Interest Calculations
-    Interest(interest::Interest),
+    Interest(Box<interest::Interest>),
     /// Currency Conversion Operations
     Currency(currency::Currency),
     /// Mileage Calculations
@@ -112,6 +113,7 @@ mod tests {
         use crate::conversions::distance::{DistanceConversion, DistanceUnits};
         use crate::conversions::{ConversionOption, Conversions};
         use crate::interest::Interest;
+        use crate::money::Money;
 
         // Test that command data is properly preserved through creation
         let command = Commands::Convert(Conversions {
@@ -136,21 +138,61 @@ mod tests {
         }
 
         // Test Interest command preserves optional fields correctly
-        let interest = Commands::Interest(Interest {
-            principal: 50000.0,
-            interest_rate: 4.5,
-            repayment: 1000.0,
-            max_repayment_pct: None,          // Testing None variant
-            annual_downpayment: Some(2500.0), // Testing Some variant
-            end_date: "2025-12-31".to_string(),
-        });
+        let interest = Commands::Interest(Box::new(Interest {
+            principal: Some(Money::from_major(50000)),
+            interest_rate: Some(4.5),
+            repayment: Some(Money::from_major(1000)),
+            annual_downpayment: Some(Money::from_major(2500)), // Testing Some variant
+            end_date: Some("2025-12-31".to_string()),
+            ..Interest::default()
+        }));
 
         if let Commands::Interest(int) = interest {
-            assert_eq!(int.principal, 50000.0);
+            assert_eq!(int.principal, Some(Money::from_major(50000)));
             assert!(int.max_repayment_pct.is_none());
-            assert_eq!(int.annual_downpayment, Some(2500.0));
+            assert_eq!(int.annual_downpayment, Some(Money::from_major(2500)));
         } else {
             panic!("Expected Interest command");
         }
     }
+
+    #[test]
+    fn test_interest_allowance_options() {
+        let parse = |args: &[&str]| {
+            Cli::try_parse_from(
+                ["lifestuff", "interest", "-p", "1000", "-i", "5"]
+                    .iter()
+                    .chain(args),
+            )
+        };
+
+        // The allowance percentage and the fixed downpayment are independent
+        assert!(parse(&[]).is_ok());
+        assert!(parse(&["-m", "10"]).is_ok());
+        assert!(parse(&["-a", "500"]).is_ok());
+        assert!(parse(&["-m", "5", "-a", "500"]).is_ok());
+
+        // A scenario stands in for the principal and rate
+        assert!(Cli::try_parse_from(["lifestuff", "interest", "--scenario", "loan.toml"]).is_ok());
+        assert!(Cli::try_parse_from(["lifestuff", "interest", "-m", "10"]).is_err());
+    }
+
+    #[test]
+    fn test_currency_subcommand_options() {
+        let parse =
+            |args: &[&str]| Cli::try_parse_from(["lifestuff", "currency"].iter().chain(args));
+
+        assert!(parse(&["--from", "GBP", "--amt", "100", "--to", "EUR", "--ttl", "1"]).is_ok());
+        assert!(parse(&["history", "EUR", "GBP"]).is_ok());
+        assert!(parse(&["rates", "--ttl", "1", "--cache", "rates.json"]).is_ok());
+        assert!(parse(&["history", "EUR", "GBP", "--endpoint", "http://localhost"]).is_ok());
+
+        // Conversion options aren't silently ignored by a subcommand
+        assert!(parse(&["--from", "GBP", "history", "EUR", "GBP"]).is_err());
+        assert!(parse(&["--on", "01/01/2025", "rates"]).is_err());
+        assert!(parse(&["--ttl", "1", "rates"]).is_err());
+        // History doesn't read the cache
+        assert!(parse(&["history", "EUR", "GBP", "--ttl", "1"]).is_err());
+        assert!(parse(&["history", "EUR", "GBP", "--cache", "rates.json"]).is_err());
+    }
 }
@@ -0,0 +1,383 @@
+use clap::ValueEnum;
+use serde::de::{self, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::iter::Sum;
+use std::ops::{Add, AddAssign, Neg, Sub, SubAssign};
+use std::str::FromStr;
+
+/// Number of decimal places held internally. Amounts are rounded to the penny
+/// explicitly via [`Money::round_to_penny`], and whenever they are displayed or serialised.
+const DECIMAL_PLACES: u32 = 6;
+const SCALE: i64 = 10i64.pow(DECIMAL_PLACES);
+const UNITS_PER_PENNY: i64 = SCALE / 100;
+
+/// How to round amounts that fall exactly halfway between two pennies
+#[derive(Debug, ValueEnum, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum RoundingMode {
+    /// Round halves to the nearest even penny (banker's rounding)
+    #[default]
+    #[clap(aliases = ["bankers", "half-even"])]
+    #[serde(alias = "bankers")]
+    HalfEven,
+    /// Round halves away from zero
+    HalfUp,
+}
+
+/// Fixed-point monetary amount, stored as an integer number of millionths of a unit. Arithmetic
+/// saturates at the limits of the range rather than overflowing.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Money(i64);
+
+/// Divides `value` by `divisor`, rounding the result according to `mode`
+fn div_round(value: i128, divisor: i128, mode: RoundingMode) -> i128 {
+    let quotient = value / divisor;
+    let remainder = value % divisor;
+    let twice_remainder = remainder.abs() * 2;
+
+    let round_away = match twice_remainder.cmp(&divisor.abs()) {
+        std::cmp::Ordering::Less => false,
+        std::cmp::Ordering::Greater => true,
+        std::cmp::Ordering::Equal => match mode {
+            RoundingMode::HalfUp => true,
+            RoundingMode::HalfEven => quotient % 2 != 0,
+        },
+    };
+
+    if !round_away {
+        quotient
+    } else if (value < 0) != (divisor < 0) {
+        quotient - 1
+    } else {
+        quotient + 1
+    }
+}
+
+impl Money {
+    pub const ZERO: Money = Money(0);
+
+    /// Whole units, e.g. pounds
+    pub const fn from_major(units: i64) -> Self {
+        Self(units.saturating_mul(SCALE))
+    }
+
+    /// Minor units, e.g. pence
+    pub const fn from_minor(minor_units: i64) -> Self {
+        Self(minor_units.saturating_mul(UNITS_PER_PENNY))
+    }
+
+    /// Converts a floating point amount, rounding to the nearest internal unit
+    pub fn from_f64(value: f64) -> Self {
+        Self((value * SCALE as f64).round_ties_even() as i64)
+    }
+
+    pub fn to_f64(self) -> f64 {
+        self.0 as f64 / SCALE as f64
+    }
+
+    /// The amount in minor units (pence), rounded according to `mode`
+    pub fn to_minor(self, mode: RoundingMode) -> i64 {
+        div_round(self.0.into(), UNITS_PER_PENNY.into(), mode) as i64
+    }
+
+    pub fn round_to_penny(self, mode: RoundingMode) -> Self {
+        Self::from_minor(self.to_minor(mode))
+    }
+
+    /// Multiplies the amount by a rate or ratio, e.g. a daily interest rate
+    pub fn mul_f64(self, factor: f64) -> Self {
+        // Float to integer casts saturate
+        Self((self.0 as f64 * factor).round_ties_even() as i64)
+    }
+
+    /// The given percentage of the amount, rounded to the penny
+    pub fn percentage(self, pct: f64, mode: RoundingMode) -> Self {
+        self.mul_f64(pct / 100.0).round_to_penny(mode)
+    }
+
+    pub fn is_positive(self) -> bool {
+        self.0 > 0
+    }
+
+    pub fn is_negative(self) -> bool {
+        self.0 < 0
+    }
+
+    pub fn is_zero(self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn abs(self) -> Self {
+        Self(self.0.saturating_abs())
+    }
+}
+
+impl Add for Money {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self(self.0.saturating_add(rhs.0))
+    }
+}
+
+impl AddAssign for Money {
+    fn add_assign(&mut self, rhs: Self) {
+        self.0 = self.0.saturating_add(rhs.0);
+    }
+}
+
+impl Sub for Money {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Self(self.0.saturating_sub(rhs.0))
+    }
+}
+
+impl SubAssign for Money {
+    fn sub_assign(&mut self, rhs: Self) {
+        self.0 = self.0.saturating_sub(rhs.0);
+    }
+}
+
+impl Neg for Money {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Self(self.0.saturating_neg())
+    }
+}
+
+impl Sum for Money {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::ZERO, Add::add)
+    }
+}
+
+impl<'a> Sum<&'a Money> for Money {
+    fn sum<I: Iterator<Item = &'a Money>>(iter: I) -> Self {
+        iter.copied().sum()
+    }
+}
+
+/// Formats with 2 decimal places (banker's rounding) unless a precision is given
+impl fmt::Display for Money {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let decimals = f.precision().unwrap_or(2).min(DECIMAL_PLACES as usize) as u32;
+        let divisor = 10i64.pow(DECIMAL_PLACES - decimals);
+        let rounded = div_round(self.0.into(), divisor.into(), RoundingMode::HalfEven);
+
+        let sign = if rounded < 0 { "-" } else { "" };
+        let rounded = rounded.unsigned_abs();
+        let whole = rounded / 10u128.pow(decimals);
+        let formatted = if decimals == 0 {
+            format!("{sign}{whole}")
+        } else {
+            let fraction = rounded % 10u128.pow(decimals);
+            format!(
+                "{sign}{whole}.{fraction:0>width$}",
+                width = decimals as usize
+            )
+        };
+
+        let padding = f.width().unwrap_or(0).saturating_sub(formatted.len());
+        match f.align() {
+            Some(fmt::Alignment::Left) => write!(f, "{formatted}{:padding$}", ""),
+            Some(fmt::Alignment::Center) => write!(
+                f,
+                "{:left$}{formatted}{:right$}",
+                "",
+                "",
+                left = padding / 2,
+                right = padding - padding / 2
+            ),
+            _ => write!(f, "{:padding$}{formatted}", ""),
+        }
+    }
+}
+
+impl FromStr for Money {
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let cleaned = input.trim().replace([',', '_', '£', '$'], "");
+        let (negative, digits) = match cleaned.strip_prefix('-') {
+            Some(digits) => (true, digits),
+            None => (false, cleaned.as_str()),
+        };
+        let (whole, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+
+        let is_digits = |part: &str| part.chars().all(|c| c.is_ascii_digit());
+        if (whole.is_empty() && fraction.is_empty()) || !is_digits(whole) || !is_digits(fraction) {
+            return Err(format!("Invalid amount '{input}'"));
+        }
+        if fraction.len() > DECIMAL_PLACES as usize {
+            return Err(format!(
+                "Invalid amount '{input}'. At most {DECIMAL_PLACES} decimal places are supported"
+            ));
+        }
+
+        let whole = if whole.is_empty() {
+            0
+        } else {
+            whole
+                .parse::<i64>()
+                .map_err(|_| format!("Amount '{input}' is too large"))?
+        };
+        let fraction = format!("{fraction:0<width$}", width = DECIMAL_PLACES as usize)
+            .parse::<i64>()
+            .unwrap_or_default();
+
+        let units = whole
+            .checked_mul(SCALE)
+            .and_then(|units| units.checked_add(fraction))
+            .ok_or_else(|| format!("Amount '{input}' is too large"))?;
+
+        Ok(Self(if negative { -units } else { units }))
+    }
+}
+
+/// Serialised as a JSON number rounded to the penny
+impl Serialize for Money {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_f64(self.round_to_penny(RoundingMode::HalfEven).to_f64())
+    }
+}
+
+struct MoneyVisitor;
+
+impl Visitor<'_> for MoneyVisitor {
+    type Value = Money;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a monetary amount as a number or string")
+    }
+
+    fn visit_f64<E: de::Error>(self, value: f64) -> Result<Self::Value, E> {
+        Ok(Money::from_f64(value))
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<Self::Value, E> {
+        Ok(Money::from_major(value))
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<Self::Value, E> {
+        Ok(Money::from_major(value as i64))
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<Self::Value, E> {
+        value.parse().map_err(E::custom)
+    }
+}
+
+impl<'de> Deserialize<'de> for Money {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(MoneyVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_display() {
+        assert_eq!("1234.56".parse::<Money>().unwrap().to_string(), "1234.56");
+        assert_eq!(
+            "£250,000".parse::<Money>().unwrap().to_string(),
+            "250000.00"
+        );
+        assert_eq!("-0.5".parse::<Money>().unwrap().to_string(), "-0.50");
+        assert_eq!(".25".parse::<Money>().unwrap(), Money::from_minor(25));
+        assert!("12.3.4".parse::<Money>().is_err());
+        assert!("abc".parse::<Money>().is_err());
+        assert!("1.0000001".parse::<Money>().is_err());
+    }
+
+    #[test]
+    fn test_arithmetic_saturates() {
+        let max = Money(i64::MAX);
+        let min = Money(i64::MIN);
+        assert_eq!(max + Money::from_major(1), max);
+        assert_eq!(min - Money::from_major(1), min);
+        let mut balance = max;
+        balance += max;
+        assert_eq!(balance, max);
+        balance -= min;
+        assert_eq!(balance, max);
+        assert_eq!(-min, max);
+        assert_eq!(min.abs(), max);
+        assert_eq!(max.mul_f64(2.0), max);
+        assert_eq!(Money::from_major(i64::MAX), max);
+        assert_eq!([max, max].iter().sum::<Money>(), max);
+    }
+
+    #[test]
+    fn test_display_honours_width_and_precision() {
+        let amount = Money::from_f64(1234.5678);
+        assert_eq!(format!("{amount:>10}"), "   1234.57");
+        assert_eq!(format!("{amount:<10.1}"), "1234.6    ");
+        assert_eq!(format!("{amount:.0}"), "1235");
+    }
+
+    #[test]
+    fn test_rounding_modes() {
+        let half = Money::from_f64(2.125);
+        assert_eq!(
+            half.round_to_penny(RoundingMode::HalfEven),
+            Money::from_minor(212)
+        );
+        assert_eq!(
+            half.round_to_penny(RoundingMode::HalfUp),
+            Money::from_minor(213)
+        );
+
+        let negative_half = Money::from_f64(-2.135);
+        assert_eq!(
+            negative_half.round_to_penny(RoundingMode::HalfEven),
+            Money::from_minor(-214)
+        );
+        assert_eq!(
+            negative_half.round_to_penny(RoundingMode::HalfUp),
+            Money::from_minor(-214)
+        );
+
+        let below_half = Money::from_f64(2.124999);
+        assert_eq!(
+            below_half.round_to_penny(RoundingMode::HalfUp),
+            Money::from_minor(212)
+        );
+    }
+
+    #[test]
+    fn test_repeated_addition_does_not_drift() {
+        let total: Money = std::iter::repeat_n(Money::from_minor(1), 1_000_000).sum();
+        assert_eq!(total, Money::from_major(10_000));
+
+        let f32_total: f32 = std::iter::repeat_n(0.01f32, 1_000_000).sum();
+        assert_ne!(f32_total, 10_000.0);
+    }
+
+    #[test]
+    fn test_percentage() {
+        let balance = Money::from_f64(250_000.55);
+        assert_eq!(
+            balance.percentage(10.0, RoundingMode::HalfEven),
+            Money::from_minor(2_500_006)
+        );
+    }
+
+    #[test]
+    fn test_serde_round_trip() {
+        let amount: Money = serde_json::from_str("1234.567").unwrap();
+        assert_eq!(amount, Money::from_f64(1234.567));
+        assert_eq!(serde_json::to_string(&amount).unwrap(), "1234.57");
+
+        let from_int: Money = serde_json::from_str("42").unwrap();
+        assert_eq!(from_int, Money::from_major(42));
+
+        let from_str: Money = serde_json::from_str("\"99.99\"").unwrap();
+        assert_eq!(from_str, Money::from_minor(9999));
+    }
+}
@@ -1,3 +1,4 @@
+use crate::money::Money;
 use clap::{Args, Subcommand};
 use serde::{Deserialize, Serialize};
 
@@ -47,10 +48,10 @@ pub struct PaymentArgs {
         long = "monthly",
         help = "Monthly payment amount (overrides default). If neither -D nor -m is specified, monthly payment is set to 0"
     )]
-    pub monthly_payment: Option<f64>,
+    pub monthly_payment: Option<Money>,
 
     #[arg(short = 'o', long, help = "Overpayment/additional payment amount")]
-    pub overpayment: Option<f64>,
+    pub overpayment: Option<Money>,
 
     #[arg(short, long, help = "Payment date in DD/MM/YYYY format")]
     pub date: String,
@@ -63,7 +64,7 @@ pub struct PaymentArgs {
 #[derive(Debug, Args, Clone)]
 pub struct InterestPostingArgs {
     #[arg(short, long, help = "Interest amount to post")]
-    pub amount: f64,
+    pub amount: Money,
 
     #[arg(short, long, help = "Posting date in DD/MM/YYYY format")]
     pub date: String,
@@ -78,9 +79,9 @@ pub struct InterestPostingArgs {
 pub struct PaymentRequest {
     pub payment_date: String,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub scheduled_payment: Option<f64>,
+    pub scheduled_payment: Option<Money>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub additional_payment: Option<f64>,
+    pub additional_payment: Option<Money>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub notes: Option<String>,
 }
@@ -88,9 +89,9 @@ pub struct PaymentRequest {
 #[derive(Debug, Serialize)]
 pub struct InterestPostingRequest {
     pub posting_date: String,
-    pub interest_amount: f64,
+    pub interest_amount: Money,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub remaining_balance: Option<f64>,
+    pub remaining_balance: Option<Money>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub notes: Option<String>,
 }
@@ -100,10 +101,10 @@ pub struct InterestPostingRequest {
 #[derive(Debug, Deserialize, Serialize)]
 pub struct MortgageSummary {
     pub id: Option<i64>,
-    pub initial_principal: f64,
+    pub initial_principal: Money,
     pub interest_rate: f64,
     pub term_years: i32,
-    pub monthly_payment: f64,
+    pub monthly_payment: Money,
     pub start_date: String,
     pub end_date: Option<String>,
     pub status: String,
@@ -111,9 +112,9 @@ pub struct MortgageSummary {
     pub notes: Option<String>,
     pub created_at: Option<String>,
     pub payments_made: i32,
-    pub total_paid: f64,
-    pub extra_payments: f64,
-    pub current_balance: f64,
+    pub total_paid: Money,
+    pub extra_payments: Money,
+    pub current_balance: Money,
     pub last_payment_date: String,
 }
 
@@ -122,12 +123,12 @@ pub struct PaymentRecord {
     pub id: Option<i64>,
     pub mortgage_id: i64,
     pub payment_date: String,
-    pub scheduled_payment: f64,
-    pub additional_payment: f64,
-    pub total_payment: f64,
-    pub principal_portion: f64,
-    pub interest_portion: f64,
-    pub remaining_balance: f64,
+    pub scheduled_payment: Money,
+    pub additional_payment: Money,
+    pub total_payment: Money,
+    pub principal_portion: Money,
+    pub interest_portion: Money,
+    pub remaining_balance: Money,
     pub payment_number: Option<i32>,
     pub notes: Option<String>,
     pub created_at: Option<String>,
@@ -138,8 +139,8 @@ pub struct InterestPosting {
     pub id: Option<i64>,
     pub mortgage_id: i64,
     pub posting_date: String,
-    pub interest_amount: f64,
-    pub remaining_balance: f64,
+    pub interest_amount: Money,
+    pub remaining_balance: Money,
     pub notes: Option<String>,
     pub created_at: Option<String>,
 }
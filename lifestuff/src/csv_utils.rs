@@ -0,0 +1,54 @@
+// CSV utility functions for schedule files
+
+use anyhow::{Result, anyhow};
+
+/// Parses each row of a CSV file with `parse_row`, skipping blank lines and `#` comments. The
+/// first remaining line is skipped as a header when it starts with a letter. Any other row that
+/// doesn't parse is an error naming its line number.
+pub(crate) fn parse_rows<T>(
+    contents: &str,
+    mut parse_row: impl FnMut(&str) -> Result<T>,
+) -> Result<Vec<T>> {
+    let mut rows = Vec::new();
+    let mut first_row = true;
+    for (line_number, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if std::mem::take(&mut first_row) && line.starts_with(char::is_alphabetic) {
+            continue;
+        }
+
+        rows.push(parse_row(line).map_err(|error| anyhow!("Line {}: {error:#}", line_number + 1))?);
+    }
+
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_numbers(contents: &str) -> Result<Vec<u32>> {
+        parse_rows(contents, |line| Ok(line.parse::<u32>()?))
+    }
+
+    #[test]
+    fn test_parse_rows_skips_a_header_blank_lines_and_comments() {
+        assert_eq!(
+            parse_numbers("\n# numbers\nvalue\n1\n\n# more\n2\n").unwrap(),
+            vec![1, 2]
+        );
+        assert_eq!(parse_numbers("1\n2\n").unwrap(), vec![1, 2]);
+    }
+
+    #[test]
+    fn test_parse_rows_reports_the_line_of_a_bad_row() {
+        // Only the first row can be a header
+        let error = parse_numbers("value\n1\nx2\n").unwrap_err();
+        assert_eq!(error.to_string(), "Line 3: invalid digit found in string");
+        let error = parse_numbers("1\nvalue\n").unwrap_err();
+        assert!(error.to_string().starts_with("Line 2: "));
+    }
+}
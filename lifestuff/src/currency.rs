@@ -1,12 +1,19 @@
 use crate::http_utils;
-use anyhow::{Context, Result, ensure};
+use anyhow::{Context, Result, bail, ensure};
+mod cache;
+mod history;
+mod rates;
 mod tests;
 
-use lifestuff_types::currency::Currency;
+use crate::dateinfo::get_date_from_string_arg;
+use cache::{RateCache, format_age, get_cache_path};
+use lifestuff_types::currency::{Currency, CurrencyOperation};
+use lifestuff_types::money::{Money, RoundingMode};
 use reqwest::StatusCode;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::time::Duration;
+use time::{Date, OffsetDateTime};
 
 pub(super) const DEFAULT_API_HOST: &str = "http://localhost:8787";
 pub(super) const ENV_VAR_NAME: &str = "LIFESTUFF_API_ENDPOINT";
@@ -14,10 +21,15 @@ pub(super) const ENV_VAR_NAME: &str = "LIFESTUFF_API_ENDPOINT";
 #[allow(non_camel_case_types)]
 #[derive(Deserialize, Serialize, Debug)]
 enum ResponseMessage {
-    success { message: String, rate: f32 },
+    success { message: String, rate: f64 },
     error { message: String },
 }
 
+/// Currency codes are three letters, e.g. GBP
+fn is_currency_code(code: &str) -> bool {
+    code.len() == 3 && code.chars().all(|c| c.is_ascii_alphabetic())
+}
+
 /// Get the base URL for the currency API
 pub(super) fn get_base_url(cli_endpoint: Option<String>) -> String {
     // First priority: explicit CLI argument
@@ -36,59 +48,136 @@ pub(super) fn get_base_url(cli_endpoint: Option<String>) -> String {
     DEFAULT_API_HOST.to_string()
 }
 
-pub fn handle_currency_operations(currency_args: Currency, verbose: bool) -> Result<()> {
-    ensure!(
-        currency_args.from.len() == 3,
-        "Invalid currency \"{}\" passed you Jabroni!",
-        currency_args.from
-    );
+/// Where a rate came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum RateSource {
+    /// Fetched from the API
+    Live,
+    /// Cached less than the TTL ago
+    Cached,
+    /// Cached longer ago than the TTL, but the API couldn't be reached
+    Offline,
+    /// Fetched from the API for a past date
+    Historical(Date),
+}
 
-    ensure!(
-        currency_args.to.iter().all(|currency| currency.len() == 3),
-        "Invalid destination currency passed.... you Jabroni!!"
-    );
+/// An exchange rate, where it came from and how many seconds old it is
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) struct Quote {
+    /// Units of the target currency per unit of the source currency
+    pub rate: f64,
+    pub source: RateSource,
+    pub age: i64,
+}
 
-    let base_url = get_base_url(currency_args.endpoint);
-    let normalized_url = http_utils::normalize_api_url(base_url);
+impl Quote {
+    fn describe_age(&self) -> String {
+        match self.source {
+            RateSource::Live => "live".to_string(),
+            RateSource::Cached => format_age(self.age),
+            RateSource::Offline => format!("{} (offline)", format_age(self.age)),
+            RateSource::Historical(date) => date.to_string(),
+        }
+    }
+}
 
-    let target_url = format!("{}/currency", normalized_url);
+/// The rate and converted amount for one target currency
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct Conversion {
+    pub target: String,
+    pub amount: Money,
+    pub quote: Quote,
+}
 
-    if verbose {
-        println!("Using currency API at: {}", normalized_url);
-        println!("target url = {target_url}");
-    }
+/// Everything needed to look rates up from the currency API
+pub(super) struct RateApi {
+    pub client: reqwest::blocking::Client,
+    pub base_url: String,
+    /// Cached rates younger than this are used without asking the API
+    pub ttl_seconds: i64,
+    /// Seconds since the Unix epoch
+    pub now: i64,
+    /// Past date to request rates for, instead of the latest
+    pub on: Option<Date>,
+    pub verbose: bool,
+}
+
+impl RateApi {
+    fn new(
+        endpoint: Option<String>,
+        ttl_hours: u64,
+        on: Option<Date>,
+        verbose: bool,
+    ) -> Result<Self> {
+        let normalized_url = http_utils::normalize_api_url(get_base_url(endpoint));
 
-    let is_localhost = http_utils::is_localhost_url(&normalized_url);
-    let mut client_builder = reqwest::blocking::Client::builder().timeout(Duration::from_secs(5));
-    if !is_localhost {
-        client_builder = client_builder.https_only(true);
+        if verbose {
+            println!("Using currency API at: {}", normalized_url);
+            println!("target url = {}/currency", normalized_url);
+        }
+
+        let is_localhost = http_utils::is_localhost_url(&normalized_url);
+        let mut client_builder =
+            reqwest::blocking::Client::builder().timeout(Duration::from_secs(5));
+        if !is_localhost {
+            client_builder = client_builder.https_only(true);
+        }
+
+        let client = client_builder
+            .build()
+            .context("Unable to create request buiilder for Currency request")?;
+
+        Ok(Self {
+            client,
+            base_url: normalized_url,
+            ttl_seconds: i64::try_from(ttl_hours.saturating_mul(3_600)).unwrap_or(i64::MAX),
+            now: OffsetDateTime::now_utc().unix_timestamp(),
+            on,
+            verbose,
+        })
     }
+}
 
-    let client = client_builder
-        .build()
-        .context("Unable to create request buiilder for Currency request")?;
+/// What the currency API said about a rate
+pub(super) enum RateResponse {
+    Rate(f64),
+    Rejected(String),
+}
 
+/// Requests the exchange rate from `source` to a single `target` currency. Fails when the API
+/// can't be reached or doesn't answer properly.
+pub(super) fn fetch_rate(
+    api: &RateApi,
+    source: &str,
+    target: &str,
+    amount: Money,
+) -> Result<RateResponse> {
     let mut json_body_map = HashMap::new();
-    json_body_map.insert("target", currency_args.to[0].to_uppercase());
-    json_body_map.insert("source", currency_args.from.to_uppercase());
-    json_body_map.insert("amount", currency_args.amt.abs().to_string());
+    json_body_map.insert("target", target.to_string());
+    json_body_map.insert("source", source.to_string());
+    json_body_map.insert("amount", amount.abs().to_string());
+    if let Some(date) = api.on {
+        json_body_map.insert("date", date.to_string());
+    }
 
-    let response = client
-        .post(target_url)
-        .headers(http_utils::build_request_headers(&normalized_url))
+    let response = api
+        .client
+        .post(format!("{}/currency", api.base_url))
+        .headers(http_utils::build_request_headers(&api.base_url))
         .json(&json_body_map)
         .send()
-        .context("Unable to send request to get currency exchange rate")?;
+        .with_context(|| format!("Unable to send request to get the {source} to {target} rate"))?;
 
     ensure!(
         response.status() == StatusCode::OK,
-        "Got a bad response code from currency API: {}",
+        "Got a bad response code from currency API for {}: {}",
+        target,
         response.status()
     );
 
     let response_body = response.text()?;
-    if verbose {
-        println!("So the response from the backend was {response_body}");
+    if api.verbose {
+        println!("So the response from the backend for {target} was {response_body}");
     }
 
     let api_response: ResponseMessage =
@@ -96,17 +185,205 @@ pub fn handle_currency_operations(currency_args: Currency, verbose: bool) -> Res
 
     match api_response {
         ResponseMessage::success { message, rate } => {
-            for currency_target in currency_args.to.iter() {
-                println!(
-                    "{:?} at a rate of 1 {} = {} {}",
-                    message, currency_args.from, rate, currency_target
-                );
+            if api.verbose {
+                println!("{target}: {message}");
+            }
+            Ok(RateResponse::Rate(rate))
+        }
+        ResponseMessage::error { message } => Ok(RateResponse::Rejected(message)),
+    }
+}
+
+/// Looks up the rate from `source` to `target`. A cached rate is used while it is within the
+/// TTL. Otherwise the rate is fetched, falling back to the cached rate however old it is when
+/// the API can't be reached. Rates for a past date are always fetched, as the cache only holds
+/// the latest ones.
+fn lookup_rate(
+    api: &RateApi,
+    cache: &mut RateCache,
+    source: &str,
+    target: &str,
+    amount: Money,
+) -> Result<Quote> {
+    let cached = match api.on {
+        Some(_) => None,
+        None => cache.get(source, target),
+    };
+    if let Some(cached) = cached
+        && cached.age(api.now) < api.ttl_seconds
+    {
+        return Ok(Quote {
+            rate: cached.rate,
+            source: RateSource::Cached,
+            age: cached.age(api.now),
+        });
+    }
+
+    match (fetch_rate(api, source, target, amount), cached) {
+        (Ok(RateResponse::Rate(rate)), _) => match api.on {
+            Some(date) => Ok(Quote {
+                rate,
+                source: RateSource::Historical(date),
+                age: 0,
+            }),
+            None => {
+                cache.insert(source, target, rate, api.now);
+                Ok(Quote {
+                    rate,
+                    source: RateSource::Live,
+                    age: 0,
+                })
+            }
+        },
+        (Ok(RateResponse::Rejected(message)), _) => {
+            bail!("Currency API error for {}: {}", target, message)
+        }
+        (Err(error), Some(cached)) => {
+            eprintln!(
+                "Warning: couldn't reach the currency API, using the {} rate cached {}",
+                cache::pair_key(source, target),
+                format_age(cached.age(api.now))
+            );
+            if api.verbose {
+                println!("{error:?}");
             }
+            Ok(Quote {
+                rate: cached.rate,
+                source: RateSource::Offline,
+                age: cached.age(api.now),
+            })
         }
-        ResponseMessage::error { message } => {
-            anyhow::bail!("Currency API error: {}", message);
+        (Err(error), None) => Err(error),
+    }
+}
+
+/// Converts `amount` into each target currency, looking each rate up separately. Repeated
+/// targets are only converted once.
+fn convert_all(
+    api: &RateApi,
+    cache: &mut RateCache,
+    source: &str,
+    targets: &[String],
+    amount: Money,
+) -> Result<Vec<Conversion>> {
+    let mut conversions: Vec<Conversion> = Vec::with_capacity(targets.len());
+    for target in targets.iter().map(|target| target.to_uppercase()) {
+        if conversions
+            .iter()
+            .any(|conversion| conversion.target == target)
+        {
+            continue;
         }
+
+        let quote = lookup_rate(api, cache, source, &target, amount)?;
+        conversions.push(Conversion {
+            amount: amount
+                .mul_f64(quote.rate)
+                .round_to_penny(RoundingMode::HalfEven),
+            target,
+            quote,
+        });
+    }
+
+    Ok(conversions)
+}
+
+pub(super) fn render_conversions(
+    source: &str,
+    amount: Money,
+    conversions: &[Conversion],
+) -> String {
+    let mut output = format!(
+        "{:.2} {} converts to:\n{:<10}{:>16}{:>16}  {}\n",
+        amount,
+        source,
+        "Currency",
+        "Amount",
+        format!("Rate (1 {source})"),
+        "Rate as of"
+    );
+    for conversion in conversions {
+        output.push_str(&format!(
+            "{:<10}{:>16.2}{:>16.4}  {}\n",
+            conversion.target,
+            conversion.amount,
+            conversion.quote.rate,
+            conversion.quote.describe_age()
+        ));
+    }
+
+    output
+}
+
+/// Saves the cache, warning rather than failing as the rates have already been shown
+fn save_cache(cache: &RateCache) {
+    if let Err(error) = cache.save() {
+        eprintln!("Warning: {error}");
+    }
+}
+
+pub fn handle_currency_operations(currency_args: Currency, verbose: bool) -> Result<()> {
+    match &currency_args.operation {
+        Some(CurrencyOperation::Rates(rates_args)) => {
+            let api = RateApi::new(currency_args.endpoint, rates_args.ttl, None, verbose)?;
+            let mut cache = RateCache::load(get_cache_path(rates_args.cache.clone()), verbose);
+            let output = rates::handle_rates(rates_args, &api, &mut cache)?;
+            print!("{}", output);
+            return Ok(());
+        }
+        Some(CurrencyOperation::History(history_args)) => {
+            let api = RateApi::new(currency_args.endpoint, currency_args.ttl, None, verbose)?;
+            let output = history::handle_history(history_args, &api)?;
+            print!("{}", output);
+            return Ok(());
+        }
+        None => {}
+    }
+
+    let from = currency_args.from.context("--from is required")?;
+    let amount = currency_args.amt.context("--amt is required")?;
+    ensure!(
+        is_currency_code(&from),
+        "Invalid currency \"{}\" passed you Jabroni!",
+        from
+    );
+
+    ensure!(
+        !currency_args.to.is_empty(),
+        "No destination currency passed. Use --to, which can be given several times"
+    );
+    ensure!(
+        currency_args
+            .to
+            .iter()
+            .all(|currency| is_currency_code(currency)),
+        "Invalid destination currency passed.... you Jabroni!!"
+    );
+
+    let on = match &currency_args.on {
+        Some(on) => {
+            let date = get_date_from_string_arg(Some(on), verbose)?.date();
+            ensure!(
+                date <= OffsetDateTime::now_utc().date(),
+                "No rates for {} yet, it's in the future",
+                date
+            );
+            Some(date)
+        }
+        None => None,
+    };
+
+    let api = RateApi::new(currency_args.endpoint, currency_args.ttl, on, verbose)?;
+    let mut cache = RateCache::load(get_cache_path(currency_args.cache), verbose);
+    let source = from.to_uppercase();
+    let conversions = convert_all(&api, &mut cache, &source, &currency_args.to, amount)?;
+    if conversions
+        .iter()
+        .any(|conversion| conversion.quote.source == RateSource::Live)
+    {
+        save_cache(&cache);
     }
+    print!("{}", render_conversions(&source, amount, &conversions));
 
     Ok(())
 }
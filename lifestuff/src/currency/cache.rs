@@ -0,0 +1,150 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub(super) const CACHE_DIR_ENV_VAR: &str = "LIFESTUFF_CACHE_DIR";
+const CACHE_FILE_NAME: &str = "currency_rates.json";
+
+/// An exchange rate and when it was fetched, in seconds since the Unix epoch
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub(super) struct CachedRate {
+    pub rate: f64,
+    pub fetched_at: i64,
+}
+
+impl CachedRate {
+    pub fn age(&self, now: i64) -> i64 {
+        (now - self.fetched_at).max(0)
+    }
+}
+
+/// Exchange rates kept on disk, keyed by `FROM/TO` currency pair
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub(super) struct RateCache {
+    #[serde(skip)]
+    path: Option<PathBuf>,
+    rates: BTreeMap<String, CachedRate>,
+}
+
+pub(super) fn pair_key(source: &str, target: &str) -> String {
+    format!("{}/{}", source.to_uppercase(), target.to_uppercase())
+}
+
+/// Get the rate cache file location. Caching is disabled when no location can be found.
+pub(super) fn get_cache_path(cli_cache: Option<PathBuf>) -> Option<PathBuf> {
+    // First priority: explicit CLI argument
+    if let Some(path) = cli_cache {
+        return Some(path);
+    }
+
+    // Second priority: environment variable
+    if let Ok(directory) = std::env::var(CACHE_DIR_ENV_VAR)
+        && !directory.is_empty()
+    {
+        return Some(PathBuf::from(directory).join(CACHE_FILE_NAME));
+    }
+
+    // Final fallback: the user's cache directory
+    std::env::var("XDG_CACHE_HOME")
+        .ok()
+        .filter(|directory| !directory.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| {
+            std::env::var("HOME")
+                .ok()
+                .filter(|home| !home.is_empty())
+                .map(|home| Path::new(&home).join(".cache"))
+        })
+        .map(|directory| directory.join("lifestuff").join(CACHE_FILE_NAME))
+}
+
+impl RateCache {
+    /// Loads the cache, starting afresh when the file doesn't exist yet or can't be read
+    pub fn load(path: Option<PathBuf>, verbose: bool) -> Self {
+        let Some(path) = path else {
+            return Self::default();
+        };
+
+        let rates = match fs::read_to_string(&path) {
+            Ok(contents) => match serde_json::from_str::<RateCache>(&contents) {
+                Ok(cache) => cache.rates,
+                Err(error) => {
+                    eprintln!(
+                        "Warning: ignoring unreadable rate cache {}: {error}",
+                        path.display()
+                    );
+                    BTreeMap::new()
+                }
+            },
+            Err(error) => {
+                if verbose {
+                    println!("No rate cache loaded from {}: {error}", path.display());
+                }
+                BTreeMap::new()
+            }
+        };
+
+        Self {
+            path: Some(path),
+            rates,
+        }
+    }
+
+    pub fn get(&self, source: &str, target: &str) -> Option<CachedRate> {
+        self.rates.get(&pair_key(source, target)).copied()
+    }
+
+    pub fn insert(&mut self, source: &str, target: &str, rate: f64, now: i64) {
+        self.rates.insert(
+            pair_key(source, target),
+            CachedRate {
+                rate,
+                fetched_at: now,
+            },
+        );
+    }
+
+    /// Cached pairs as `(FROM, TO)`, in alphabetical order
+    pub fn pairs(&self) -> Vec<(String, String)> {
+        self.rates
+            .keys()
+            .filter_map(|key| key.split_once('/'))
+            .map(|(source, target)| (source.to_string(), target.to_string()))
+            .collect()
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(directory) = path.parent()
+            && !directory.as_os_str().is_empty()
+        {
+            fs::create_dir_all(directory).with_context(|| {
+                format!(
+                    "Unable to create rate cache directory {}",
+                    directory.display()
+                )
+            })?;
+        }
+
+        let contents =
+            serde_json::to_string_pretty(self).context("Unable to serialise the rate cache")?;
+        fs::write(path, contents)
+            .with_context(|| format!("Unable to save the rate cache to {}", path.display()))
+    }
+}
+
+/// How long ago a rate was fetched, in the largest whole unit
+pub(super) fn format_age(seconds: i64) -> String {
+    let (count, unit) = match seconds {
+        ..60 => return "just now".to_string(),
+        60..3_600 => (seconds / 60, "minute"),
+        3_600..86_400 => (seconds / 3_600, "hour"),
+        _ => (seconds / 86_400, "day"),
+    };
+
+    format!("{count} {unit}{} ago", if count == 1 { "" } else { "s" })
+}
@@ -0,0 +1,217 @@
+use super::{RateApi, is_currency_code};
+use crate::dateinfo::get_date_from_string_arg;
+use crate::http_utils;
+use anyhow::{Context, Result, anyhow, bail, ensure};
+use lifestuff_types::currency::History;
+use reqwest::StatusCode;
+use serde::{Deserialize, Deserializer};
+use std::collections::HashMap;
+use time::macros::format_description;
+use time::{Date, Duration, OffsetDateTime};
+
+/// Days of history shown when no start date is given
+const DEFAULT_HISTORY_DAYS: i64 = 30;
+/// Longer histories are averaged down to this many sparkline characters
+const SPARKLINE_WIDTH: usize = 60;
+const SPARKLINE_LEVELS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+fn deserialize_date<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Date, D::Error> {
+    let date = String::deserialize(deserializer)?;
+    Date::parse(&date, format_description!("[year]-[month]-[day]"))
+        .map_err(serde::de::Error::custom)
+}
+
+/// The rate on one day of a history
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub(super) struct HistoricalRate {
+    #[serde(deserialize_with = "deserialize_date")]
+    pub date: Date,
+    pub rate: f64,
+}
+
+#[allow(non_camel_case_types)]
+#[derive(Deserialize, Debug)]
+enum HistoryResponse {
+    success {
+        message: String,
+        rates: Vec<HistoricalRate>,
+    },
+    error {
+        message: String,
+    },
+}
+
+/// Requests the daily rates from `source` to `target` between two dates, inclusive, oldest
+/// first
+pub(super) fn fetch_history(
+    api: &RateApi,
+    source: &str,
+    target: &str,
+    from: Date,
+    to: Date,
+) -> Result<Vec<HistoricalRate>> {
+    let mut json_body_map = HashMap::new();
+    json_body_map.insert("source", source.to_string());
+    json_body_map.insert("target", target.to_string());
+    json_body_map.insert("from", from.to_string());
+    json_body_map.insert("to", to.to_string());
+
+    let response = api
+        .client
+        .post(format!("{}/currency/history", api.base_url))
+        .headers(http_utils::build_request_headers(&api.base_url))
+        .json(&json_body_map)
+        .send()
+        .with_context(|| format!("Unable to send request to get the {source}/{target} history"))?;
+
+    ensure!(
+        response.status() == StatusCode::OK,
+        "Got a bad response code from currency API for the {}/{} history: {}",
+        source,
+        target,
+        response.status()
+    );
+
+    let response_body = response.text()?;
+    if api.verbose {
+        println!("So the response from the backend was {response_body}");
+    }
+
+    let api_response: HistoryResponse = serde_json::from_str(&response_body)
+        .map_err(|error| anyhow!("Unable to parse currency API history response: {error}"))?;
+
+    let mut rates = match api_response {
+        HistoryResponse::success { message, rates } => {
+            if api.verbose {
+                println!("{message}");
+            }
+            rates
+        }
+        HistoryResponse::error { message } => {
+            bail!("Currency API error for {}/{}: {}", source, target, message)
+        }
+    };
+
+    ensure!(
+        !rates.is_empty(),
+        "No {}/{} rates between {} and {}",
+        source,
+        target,
+        from,
+        to
+    );
+    rates.sort_by_key(|rate| rate.date);
+
+    Ok(rates)
+}
+
+/// The lowest, highest and average rate over a history
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) struct RateSummary {
+    pub min: HistoricalRate,
+    pub max: HistoricalRate,
+    pub average: f64,
+}
+
+/// Summarises the rates, taking the earliest day for a tied minimum or maximum
+pub(super) fn summarise(rates: &[HistoricalRate]) -> Option<RateSummary> {
+    let first = *rates.first()?;
+    let (min, max) = rates.iter().fold((first, first), |(min, max), rate| {
+        (
+            if rate.rate < min.rate { *rate } else { min },
+            if rate.rate > max.rate { *rate } else { max },
+        )
+    });
+
+    Some(RateSummary {
+        min,
+        max,
+        average: rates.iter().map(|rate| rate.rate).sum::<f64>() / rates.len() as f64,
+    })
+}
+
+/// Draws the values as block characters scaled between their minimum and maximum, averaging
+/// neighbouring values together when there are more than `width`
+pub(super) fn sparkline(values: &[f64], width: usize) -> String {
+    let chunk_size = values.len().div_ceil(width.max(1)).max(1);
+    let points: Vec<f64> = values
+        .chunks(chunk_size)
+        .map(|chunk| chunk.iter().sum::<f64>() / chunk.len() as f64)
+        .collect();
+
+    let min = points.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = points.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let top = SPARKLINE_LEVELS.len() - 1;
+    points
+        .iter()
+        .map(|point| {
+            if max > min {
+                let level = ((point - min) / (max - min) * top as f64).round() as usize;
+                SPARKLINE_LEVELS[level.min(top)]
+            } else {
+                // A flat line sits in the middle
+                SPARKLINE_LEVELS[top / 2]
+            }
+        })
+        .collect()
+}
+
+pub(super) fn render_history(source: &str, target: &str, rates: &[HistoricalRate]) -> String {
+    let Some(summary) = summarise(rates) else {
+        return format!("No {source}/{target} rates\n");
+    };
+
+    let values: Vec<f64> = rates.iter().map(|rate| rate.rate).collect();
+    format!(
+        "{}/{} from {} to {}, {} rates\n\
+         {:<10}{:>12.4}  {}\n\
+         {:<10}{:>12.4}  {}\n\
+         {:<10}{:>12.4}\n\
+         {}\n",
+        source,
+        target,
+        rates[0].date,
+        rates[rates.len() - 1].date,
+        rates.len(),
+        "Min",
+        summary.min.rate,
+        summary.min.date,
+        "Max",
+        summary.max.rate,
+        summary.max.date,
+        "Average",
+        summary.average,
+        sparkline(&values, SPARKLINE_WIDTH)
+    )
+}
+
+pub(super) fn handle_history(args: &History, api: &RateApi) -> Result<String> {
+    let source = args.source.to_uppercase();
+    let target = args.target.to_uppercase();
+    ensure!(
+        is_currency_code(&source) && is_currency_code(&target),
+        "Invalid currency pair {}/{}. Currencies are three letters, e.g. GBP",
+        source,
+        target
+    );
+
+    let to = get_date_from_string_arg(args.to.as_deref(), api.verbose)?.date();
+    let from = match &args.from {
+        Some(from) => get_date_from_string_arg(Some(from), api.verbose)?.date(),
+        None => to - Duration::days(DEFAULT_HISTORY_DAYS),
+    };
+    ensure!(
+        from < to,
+        "The history must start before it ends, got {} to {}",
+        from,
+        to
+    );
+    ensure!(
+        to <= OffsetDateTime::now_utc().date(),
+        "No rates for {} yet, it's in the future",
+        to
+    );
+
+    let rates = fetch_history(api, &source, &target, from, to)?;
+    Ok(render_history(&source, &target, &rates))
+}
@@ -0,0 +1,121 @@
+use super::cache::{RateCache, format_age, pair_key};
+use super::{RateApi, RateResponse, fetch_rate, is_currency_code};
+use anyhow::{Result, anyhow, ensure};
+use lifestuff_types::currency::Rates;
+use lifestuff_types::money::Money;
+use time::OffsetDateTime;
+use time::macros::format_description;
+
+/// Parses a currency pair written as `GBP/EUR`, `GBP-EUR` or `GBPEUR`
+pub(super) fn parse_pair(pair: &str) -> Result<(String, String)> {
+    let pair = pair.trim().to_uppercase();
+    let (source, target) = pair
+        .split_once(['/', '-'])
+        .or_else(|| (pair.len() == 6).then(|| pair.split_at(3)))
+        .ok_or_else(|| anyhow!("Invalid currency pair \"{pair}\". Use FROM/TO, e.g. GBP/EUR"))?;
+
+    ensure!(
+        is_currency_code(source) && is_currency_code(target),
+        "Invalid currency pair \"{}\". Use FROM/TO, e.g. GBP/EUR",
+        pair
+    );
+
+    Ok((source.to_string(), target.to_string()))
+}
+
+/// Fetches the rate for each pair, warning about the ones that fail. Returns how many were
+/// fetched.
+pub(super) fn refresh_rates(
+    api: &RateApi,
+    cache: &mut RateCache,
+    pairs: &[(String, String)],
+) -> usize {
+    let mut refreshed = 0;
+    for (source, target) in pairs {
+        match fetch_rate(api, source, target, Money::from_major(1)) {
+            Ok(RateResponse::Rate(rate)) => {
+                cache.insert(source, target, rate, api.now);
+                refreshed += 1;
+            }
+            Ok(RateResponse::Rejected(message)) => eprintln!(
+                "Warning: unable to refresh {}: {}",
+                pair_key(source, target),
+                message
+            ),
+            Err(error) => eprintln!(
+                "Warning: unable to refresh {}: {:#}",
+                pair_key(source, target),
+                error
+            ),
+        }
+    }
+
+    refreshed
+}
+
+/// Lists the cached rate for each pair, marking the ones older than the TTL as stale
+pub(super) fn render_rates(api: &RateApi, cache: &RateCache, pairs: &[(String, String)]) -> String {
+    let date_format = format_description!("[year]-[month]-[day] [hour]:[minute] UTC");
+    let mut output = format!("{:<10}{:>14}  {:<22}{}\n", "Pair", "Rate", "Fetched", "Age");
+    for (source, target) in pairs {
+        let key = pair_key(source, target);
+        let Some(cached) = cache.get(source, target) else {
+            output.push_str(&format!("{:<10}{:>14}\n", key, "not cached"));
+            continue;
+        };
+
+        let fetched = OffsetDateTime::from_unix_timestamp(cached.fetched_at)
+            .ok()
+            .and_then(|fetched| fetched.format(date_format).ok())
+            .unwrap_or_else(|| "unknown".to_string());
+        let age = cached.age(api.now);
+        let stale = if age >= api.ttl_seconds {
+            " (stale)"
+        } else {
+            ""
+        };
+        output.push_str(&format!(
+            "{:<10}{:>14.4}  {:<22}{}{}\n",
+            key,
+            cached.rate,
+            fetched,
+            format_age(age),
+            stale
+        ));
+    }
+
+    output
+}
+
+/// Shows the cached rates for the requested pairs, or every cached pair, fetching them first
+/// with `--refresh`
+pub(super) fn handle_rates(args: &Rates, api: &RateApi, cache: &mut RateCache) -> Result<String> {
+    let mut pairs: Vec<(String, String)> = Vec::with_capacity(args.pairs.len());
+    for pair in &args.pairs {
+        let pair = parse_pair(pair)?;
+        if !pairs.contains(&pair) {
+            pairs.push(pair);
+        }
+    }
+    if pairs.is_empty() {
+        pairs = cache.pairs();
+    }
+
+    if args.refresh {
+        ensure!(
+            !pairs.is_empty(),
+            "No currency pairs to refresh. Pass them as FROM/TO, e.g. currency rates --refresh GBP/EUR"
+        );
+        let refreshed = refresh_rates(api, cache, &pairs);
+        ensure!(refreshed > 0, "Unable to refresh any currency rates");
+        cache.save()?;
+    }
+
+    if pairs.is_empty() {
+        return Ok(
+            "No cached rates yet. Fetch some with: currency rates --refresh GBP/EUR\n".to_string(),
+        );
+    }
+
+    Ok(render_rates(api, cache, &pairs))
+}
@@ -1,8 +1,54 @@
 #[cfg(test)]
 mod currency_tests {
-    use super::super::{DEFAULT_API_HOST, ENV_VAR_NAME, get_base_url};
-    use mockito::Server;
+    use super::super::cache::{CACHE_DIR_ENV_VAR, RateCache, format_age, get_cache_path, pair_key};
+    use super::super::history::{
+        HistoricalRate, RateSummary, fetch_history, handle_history, render_history, sparkline,
+        summarise,
+    };
+    use super::super::rates::{handle_rates, parse_pair, render_rates};
+    use super::super::{
+        Conversion, DEFAULT_API_HOST, ENV_VAR_NAME, Quote, RateApi, RateSource, convert_all,
+        get_base_url, render_conversions,
+    };
+    use lifestuff_types::currency::{History, Rates};
+    use lifestuff_types::money::Money;
+    use mockito::{Matcher, Server};
     use serial_test::serial;
+    use std::path::PathBuf;
+    use time::Date;
+    use time::macros::date;
+
+    const NOW: i64 = 1_760_000_000;
+    const HOUR: i64 = 3_600;
+
+    /// A cache file in the temp directory, so tests don't touch the real one
+    fn temp_cache(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "lifestuff-{}-{name}-currency_rates.json",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+        path
+    }
+
+    fn test_api(base_url: &str) -> RateApi {
+        RateApi {
+            client: reqwest::blocking::Client::new(),
+            base_url: base_url.to_string(),
+            ttl_seconds: 12 * HOUR,
+            now: NOW,
+            on: None,
+            verbose: false,
+        }
+    }
+
+    fn live(rate: f64) -> Quote {
+        Quote {
+            rate,
+            source: RateSource::Live,
+            age: 0,
+        }
+    }
 
     #[test]
     fn test_handle_currency_operations_valid() {
@@ -15,10 +61,14 @@ mod currency_tests {
             .create();
 
         let currency_args = lifestuff_types::currency::Currency {
-            from: "USD".to_string(),
-            amt: 100.0,
+            operation: None,
+            from: Some("USD".to_string()),
+            amt: Some(Money::from_major(100)),
             to: vec!["EUR".to_string()],
+            on: None,
             endpoint: Some(server.url()),
+            ttl: 12,
+            cache: Some(temp_cache("handle_currency_operations_valid")),
         };
         let result = crate::currency::handle_currency_operations(currency_args, false);
         assert!(result.is_ok(), "Valid currency conversion should succeed");
@@ -28,10 +78,16 @@ mod currency_tests {
     #[test]
     fn test_handle_currency_operations_invalid_from_currency() {
         let currency_args = lifestuff_types::currency::Currency {
-            from: "INVALID".to_string(),
-            amt: 100.0,
+            operation: None,
+            from: Some("INVALID".to_string()),
+            amt: Some(Money::from_major(100)),
             to: vec!["EUR".to_string()],
+            on: None,
             endpoint: None,
+            ttl: 12,
+            cache: Some(temp_cache(
+                "handle_currency_operations_invalid_from_currency",
+            )),
         };
         let result = crate::currency::handle_currency_operations(currency_args, false);
         assert!(result.is_err(), "Invalid source currency should fail");
@@ -40,15 +96,47 @@ mod currency_tests {
     #[test]
     fn test_handle_currency_operations_invalid_to_currency() {
         let currency_args = lifestuff_types::currency::Currency {
-            from: "USD".to_string(),
-            amt: 100.0,
+            operation: None,
+            from: Some("USD".to_string()),
+            amt: Some(Money::from_major(100)),
             to: vec!["INVALID".to_string()],
+            on: None,
             endpoint: None,
+            ttl: 12,
+            cache: Some(temp_cache("handle_currency_operations_invalid_to_currency")),
         };
         let result = crate::currency::handle_currency_operations(currency_args, false);
         assert!(result.is_err(), "Invalid target currency should fail");
     }
 
+    #[test]
+    fn test_handle_currency_operations_codes_must_be_letters() {
+        let currency_args = |from: &str, to: &str| lifestuff_types::currency::Currency {
+            operation: None,
+            from: Some(from.to_string()),
+            amt: Some(Money::from_major(100)),
+            to: vec![to.to_string()],
+            on: None,
+            endpoint: None,
+            ttl: 12,
+            cache: Some(temp_cache(
+                "handle_currency_operations_codes_must_be_letters",
+            )),
+        };
+        let error = crate::currency::handle_currency_operations(currency_args("G1P", "EUR"), false)
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Invalid currency \"G1P\" passed you Jabroni!"
+        );
+        let error = crate::currency::handle_currency_operations(currency_args("GBP", "E2R"), false)
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Invalid destination currency passed.... you Jabroni!!"
+        );
+    }
+
     #[test]
     fn test_handle_currency_operations_zero_amount() {
         let mut server = Server::new();
@@ -60,10 +148,14 @@ mod currency_tests {
             .create();
 
         let currency_args = lifestuff_types::currency::Currency {
-            from: "USD".to_string(),
-            amt: 0.0,
+            operation: None,
+            from: Some("USD".to_string()),
+            amt: Some(Money::ZERO),
             to: vec!["EUR".to_string()],
+            on: None,
             endpoint: Some(server.url()),
+            ttl: 12,
+            cache: Some(temp_cache("handle_currency_operations_zero_amount")),
         };
         let result = crate::currency::handle_currency_operations(currency_args, false);
         assert!(result.is_ok(), "Zero amount conversion should succeed");
@@ -81,10 +173,14 @@ mod currency_tests {
             .create();
 
         let currency_args = lifestuff_types::currency::Currency {
-            from: "USD".to_string(),
-            amt: -100.0,
+            operation: None,
+            from: Some("USD".to_string()),
+            amt: Some(Money::from_major(-100)),
             to: vec!["EUR".to_string()],
+            on: None,
             endpoint: Some(server.url()),
+            ttl: 12,
+            cache: Some(temp_cache("handle_currency_operations_negative_amount")),
         };
         let result = crate::currency::handle_currency_operations(currency_args, false);
         assert!(result.is_ok(), "Negative amount conversion should succeed");
@@ -190,10 +286,14 @@ mod currency_tests {
         // This test verifies URL normalization but won't make actual network calls
         // since we can't easily mock HTTPS enforcement without a real server
         let currency_args = lifestuff_types::currency::Currency {
-            from: "USD".to_string(),
-            amt: 100.0,
+            operation: None,
+            from: Some("USD".to_string()),
+            amt: Some(Money::from_major(100)),
             to: vec!["EUR".to_string()],
+            on: None,
             endpoint: Some("http://api.example.com".to_string()),
+            ttl: 12,
+            cache: Some(temp_cache("https_enforcement_for_remote_http_url")),
         };
 
         // The function will attempt to connect and fail (no server), but we can verify
@@ -209,10 +309,14 @@ mod currency_tests {
     #[test]
     fn test_https_enforcement_for_remote_no_protocol() {
         let currency_args = lifestuff_types::currency::Currency {
-            from: "USD".to_string(),
-            amt: 100.0,
+            operation: None,
+            from: Some("USD".to_string()),
+            amt: Some(Money::from_major(100)),
             to: vec!["EUR".to_string()],
+            on: None,
             endpoint: Some("api.example.com".to_string()),
+            ttl: 12,
+            cache: Some(temp_cache("https_enforcement_for_remote_no_protocol")),
         };
 
         let result = crate::currency::handle_currency_operations(currency_args, false);
@@ -232,14 +336,658 @@ mod currency_tests {
 
         // mockito server.url() returns http://127.0.0.1:port
         let currency_args = lifestuff_types::currency::Currency {
-            from: "USD".to_string(),
-            amt: 100.0,
+            operation: None,
+            from: Some("USD".to_string()),
+            amt: Some(Money::from_major(100)),
             to: vec!["EUR".to_string()],
+            on: None,
             endpoint: Some(server.url()),
+            ttl: 12,
+            cache: Some(temp_cache("localhost_allows_http")),
         };
 
         let result = crate::currency::handle_currency_operations(currency_args, false);
         assert!(result.is_ok(), "Localhost HTTP should be allowed");
         mock.assert();
     }
+
+    fn mock_rate(server: &mut Server, target: &str, rate: f64) -> mockito::Mock {
+        server
+            .mock("POST", "/currency")
+            .match_body(Matcher::PartialJsonString(format!(
+                r#"{{"source":"USD","target":"{target}"}}"#
+            )))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(format!(
+                r#"{{"success":{{"message":"Converted","rate":{rate}}}}}"#
+            ))
+            .create()
+    }
+
+    #[test]
+    fn test_convert_all_requests_each_target() {
+        let mut server = Server::new();
+        let eur = mock_rate(&mut server, "EUR", 0.92);
+        let gbp = mock_rate(&mut server, "GBP", 0.79);
+        let jpy = mock_rate(&mut server, "JPY", 157.235);
+
+        let mut cache = RateCache::default();
+        let targets = ["eur", "GBP", "JPY", "EUR"].map(String::from);
+        let conversions = convert_all(
+            &test_api(&server.url()),
+            &mut cache,
+            "USD",
+            &targets,
+            Money::from_major(250),
+        )
+        .unwrap();
+
+        assert_eq!(
+            conversions,
+            vec![
+                Conversion {
+                    target: "EUR".to_string(),
+                    amount: Money::from_major(230),
+                    quote: live(0.92),
+                },
+                Conversion {
+                    target: "GBP".to_string(),
+                    amount: "197.50".parse().unwrap(),
+                    quote: live(0.79),
+                },
+                Conversion {
+                    target: "JPY".to_string(),
+                    amount: "39308.75".parse().unwrap(),
+                    quote: live(157.235),
+                },
+            ]
+        );
+        // Repeated targets are only requested once
+        eur.expect(1).assert();
+        gbp.assert();
+        jpy.assert();
+        // Fetched rates are cached
+        assert_eq!(
+            cache.get("USD", "GBP").map(|cached| cached.rate),
+            Some(0.79)
+        );
+        assert_eq!(
+            cache.get("usd", "jpy").map(|cached| cached.fetched_at),
+            Some(NOW)
+        );
+    }
+
+    #[test]
+    fn test_convert_all_reports_the_failing_target() {
+        let mut server = Server::new();
+        let _eur = mock_rate(&mut server, "EUR", 0.92);
+        let _xyz = server
+            .mock("POST", "/currency")
+            .match_body(Matcher::PartialJsonString(
+                r#"{"target":"XYZ"}"#.to_string(),
+            ))
+            .with_status(200)
+            .with_body(r#"{"error":{"message":"Unknown currency"}}"#)
+            .create();
+
+        let error = convert_all(
+            &test_api(&server.url()),
+            &mut RateCache::default(),
+            "USD",
+            &["EUR".to_string(), "XYZ".to_string()],
+            Money::from_major(100),
+        )
+        .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Currency API error for XYZ: Unknown currency"
+        );
+    }
+
+    #[test]
+    fn test_convert_all_uses_fresh_cached_rates() {
+        let mut server = Server::new();
+        let eur = mock_rate(&mut server, "EUR", 0.95);
+
+        let mut cache = RateCache::default();
+        cache.insert("USD", "EUR", 0.92, NOW - 2 * HOUR);
+        let conversions = convert_all(
+            &test_api(&server.url()),
+            &mut cache,
+            "USD",
+            &["EUR".to_string()],
+            Money::from_major(100),
+        )
+        .unwrap();
+
+        assert_eq!(
+            conversions[0].quote,
+            Quote {
+                rate: 0.92,
+                source: RateSource::Cached,
+                age: 2 * HOUR,
+            }
+        );
+        assert_eq!(conversions[0].amount, Money::from_major(92));
+        eur.expect(0).assert();
+    }
+
+    #[test]
+    fn test_convert_all_refetches_stale_cached_rates() {
+        let mut server = Server::new();
+        let eur = mock_rate(&mut server, "EUR", 0.95);
+
+        let mut cache = RateCache::default();
+        cache.insert("USD", "EUR", 0.92, NOW - 12 * HOUR);
+        let conversions = convert_all(
+            &test_api(&server.url()),
+            &mut cache,
+            "USD",
+            &["EUR".to_string()],
+            Money::from_major(100),
+        )
+        .unwrap();
+
+        assert_eq!(conversions[0].quote, live(0.95));
+        assert_eq!(
+            cache.get("USD", "EUR").map(|cached| cached.rate),
+            Some(0.95)
+        );
+        eur.assert();
+    }
+
+    #[test]
+    fn test_convert_all_falls_back_to_stale_rates_when_offline() {
+        let mut server = Server::new();
+        let _unavailable = server.mock("POST", "/currency").with_status(503).create();
+
+        let mut cache = RateCache::default();
+        cache.insert("USD", "EUR", 0.92, NOW - 3 * 24 * HOUR);
+        let api = test_api(&server.url());
+        let conversions = convert_all(
+            &api,
+            &mut cache,
+            "USD",
+            &["EUR".to_string()],
+            Money::from_major(100),
+        )
+        .unwrap();
+
+        assert_eq!(
+            conversions[0].quote,
+            Quote {
+                rate: 0.92,
+                source: RateSource::Offline,
+                age: 3 * 24 * HOUR,
+            }
+        );
+
+        // Without a cached rate there is nothing to fall back to
+        let error = convert_all(
+            &api,
+            &mut cache,
+            "USD",
+            &["GBP".to_string()],
+            Money::from_major(100),
+        )
+        .unwrap_err();
+        assert!(error.to_string().contains("GBP"), "{error}");
+    }
+
+    #[test]
+    fn test_convert_all_does_not_fall_back_when_rejected() {
+        let mut server = Server::new();
+        let _rejected = server
+            .mock("POST", "/currency")
+            .with_status(200)
+            .with_body(r#"{"error":{"message":"Unknown currency"}}"#)
+            .create();
+
+        let mut cache = RateCache::default();
+        cache.insert("USD", "EUR", 0.92, NOW - 13 * HOUR);
+        let error = convert_all(
+            &test_api(&server.url()),
+            &mut cache,
+            "USD",
+            &["EUR".to_string()],
+            Money::from_major(100),
+        )
+        .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Currency API error for EUR: Unknown currency"
+        );
+    }
+
+    #[test]
+    fn test_render_conversions() {
+        let conversions = [
+            Conversion {
+                target: "EUR".to_string(),
+                amount: Money::from_major(92),
+                quote: live(0.92),
+            },
+            Conversion {
+                target: "GBP".to_string(),
+                amount: Money::from_major(79),
+                quote: Quote {
+                    rate: 0.79,
+                    source: RateSource::Cached,
+                    age: 2 * HOUR,
+                },
+            },
+            Conversion {
+                target: "JPY".to_string(),
+                amount: "15723.50".parse().unwrap(),
+                quote: Quote {
+                    rate: 157.235,
+                    source: RateSource::Offline,
+                    age: 24 * HOUR,
+                },
+            },
+        ];
+
+        assert_eq!(
+            render_conversions("USD", Money::from_major(100), &conversions),
+            "100.00 USD converts to:\n\
+             Currency            Amount    Rate (1 USD)  Rate as of\n\
+             EUR                  92.00          0.9200  live\n\
+             GBP                  79.00          0.7900  2 hours ago\n\
+             JPY               15723.50        157.2350  1 day ago (offline)\n"
+        );
+    }
+
+    #[test]
+    fn test_format_age() {
+        assert_eq!(format_age(0), "just now");
+        assert_eq!(format_age(59), "just now");
+        assert_eq!(format_age(60), "1 minute ago");
+        assert_eq!(format_age(45 * 60), "45 minutes ago");
+        assert_eq!(format_age(HOUR), "1 hour ago");
+        assert_eq!(format_age(23 * HOUR + 59 * 60), "23 hours ago");
+        assert_eq!(format_age(50 * HOUR), "2 days ago");
+    }
+
+    #[test]
+    fn test_parse_pair() {
+        let gbp_eur = ("GBP".to_string(), "EUR".to_string());
+        assert_eq!(parse_pair("GBP/EUR").unwrap(), gbp_eur);
+        assert_eq!(parse_pair("gbp-eur").unwrap(), gbp_eur);
+        assert_eq!(parse_pair(" GBPEUR ").unwrap(), gbp_eur);
+        assert!(parse_pair("GBP").is_err());
+        assert!(parse_pair("GBP/EURO").is_err());
+        assert!(parse_pair("GB1/EUR").is_err());
+        assert_eq!(pair_key("gbp", "eur"), "GBP/EUR");
+    }
+
+    #[test]
+    fn test_rate_cache_round_trip() {
+        let path = temp_cache("rate_cache_round_trip");
+        let mut cache = RateCache::load(Some(path.clone()), false);
+        assert!(cache.pairs().is_empty(), "A missing file is an empty cache");
+
+        cache.insert("USD", "JPY", 157.235, NOW);
+        cache.insert("GBP", "EUR", 1.17, NOW - HOUR);
+        cache.save().unwrap();
+
+        let cache = RateCache::load(Some(path.clone()), false);
+        assert_eq!(
+            cache.pairs(),
+            vec![
+                ("GBP".to_string(), "EUR".to_string()),
+                ("USD".to_string(), "JPY".to_string()),
+            ]
+        );
+        assert_eq!(cache.get("GBP", "EUR").unwrap().age(NOW), HOUR);
+
+        std::fs::write(&path, "not json").unwrap();
+        assert!(
+            RateCache::load(Some(path.clone()), false)
+                .pairs()
+                .is_empty(),
+            "An unreadable cache is ignored"
+        );
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    #[serial]
+    fn test_get_cache_path_priority() {
+        let cli = PathBuf::from("/tmp/rates.json");
+        // SAFETY: This test runs serially via #[serial] to avoid data races
+        unsafe {
+            std::env::set_var(CACHE_DIR_ENV_VAR, "/tmp/lifestuff-cache");
+            std::env::set_var("XDG_CACHE_HOME", "/tmp/xdg");
+        }
+        assert_eq!(get_cache_path(Some(cli.clone())), Some(cli));
+        assert_eq!(
+            get_cache_path(None),
+            Some(PathBuf::from("/tmp/lifestuff-cache/currency_rates.json"))
+        );
+
+        // SAFETY: Also runs serially
+        unsafe {
+            std::env::remove_var(CACHE_DIR_ENV_VAR);
+        }
+        assert_eq!(
+            get_cache_path(None),
+            Some(PathBuf::from("/tmp/xdg/lifestuff/currency_rates.json"))
+        );
+
+        // SAFETY: Cleanup, also runs serially
+        unsafe {
+            std::env::remove_var("XDG_CACHE_HOME");
+        }
+    }
+
+    #[test]
+    fn test_rates_refresh() {
+        let mut server = Server::new();
+        let _eur = mock_rate(&mut server, "EUR", 0.92);
+        let jpy = mock_rate(&mut server, "JPY", 157.235);
+        let _unknown = server
+            .mock("POST", "/currency")
+            .match_body(Matcher::PartialJsonString(
+                r#"{"target":"XYZ"}"#.to_string(),
+            ))
+            .with_status(200)
+            .with_body(r#"{"error":{"message":"Unknown currency"}}"#)
+            .create();
+
+        let path = temp_cache("rates_refresh");
+        let mut cache = RateCache::load(Some(path.clone()), false);
+        cache.insert("USD", "JPY", 150.0, NOW - 2 * HOUR);
+        let api = test_api(&server.url());
+        let args = Rates {
+            pairs: ["usd/eur", "USD/JPY", "USD/XYZ"].map(String::from).to_vec(),
+            refresh: true,
+            ttl: 12,
+            cache: None,
+        };
+
+        // Fresh rates are fetched again, and failures don't stop the others
+        let output = handle_rates(&args, &api, &mut cache).unwrap();
+        jpy.assert();
+        assert_eq!(
+            output,
+            "Pair                Rate  Fetched               Age\n\
+             USD/EUR           0.9200  2025-10-09 08:53 UTC  just now\n\
+             USD/JPY         157.2350  2025-10-09 08:53 UTC  just now\n\
+             USD/XYZ       not cached\n"
+        );
+        let saved = RateCache::load(Some(path.clone()), false);
+        assert_eq!(
+            saved.get("USD", "EUR").map(|cached| cached.rate),
+            Some(0.92)
+        );
+
+        // Nothing refreshed is an error
+        let args = Rates {
+            pairs: vec!["USD/XYZ".to_string()],
+            refresh: true,
+            ttl: 12,
+            cache: None,
+        };
+        assert!(handle_rates(&args, &api, &mut cache).is_err());
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_render_rates_marks_stale_rates() {
+        let mut cache = RateCache::default();
+        cache.insert("GBP", "EUR", 1.17, NOW - 3 * HOUR);
+        cache.insert("GBP", "USD", 1.27, NOW - 2 * 24 * HOUR);
+
+        let api = test_api(DEFAULT_API_HOST);
+        assert_eq!(
+            render_rates(&api, &cache, &cache.pairs()),
+            "Pair                Rate  Fetched               Age\n\
+             GBP/EUR           1.1700  2025-10-09 05:53 UTC  3 hours ago\n\
+             GBP/USD           1.2700  2025-10-07 08:53 UTC  2 days ago (stale)\n"
+        );
+
+        let empty = Rates {
+            pairs: vec![],
+            refresh: false,
+            ttl: 12,
+            cache: None,
+        };
+        assert_eq!(
+            handle_rates(&empty, &api, &mut RateCache::default()).unwrap(),
+            "No cached rates yet. Fetch some with: currency rates --refresh GBP/EUR\n"
+        );
+    }
+
+    #[test]
+    fn test_handle_currency_operations_requires_a_target() {
+        let currency_args = lifestuff_types::currency::Currency {
+            operation: None,
+            from: Some("USD".to_string()),
+            amt: Some(Money::from_major(100)),
+            to: vec![],
+            on: None,
+            endpoint: None,
+            ttl: 12,
+            cache: Some(temp_cache("handle_currency_operations_requires_a_target")),
+        };
+        let result = crate::currency::handle_currency_operations(currency_args, false);
+        assert!(result.is_err(), "At least one target currency is needed");
+    }
+
+    #[test]
+    fn test_convert_all_on_a_past_date() {
+        let mut server = Server::new();
+        let eur = server
+            .mock("POST", "/currency")
+            .match_body(Matcher::PartialJsonString(
+                r#"{"source":"USD","target":"EUR","date":"2025-08-15"}"#.to_string(),
+            ))
+            .with_status(200)
+            .with_body(r#"{"success":{"message":"Converted","rate":0.86}}"#)
+            .create();
+
+        // The cached rate is the latest one, so isn't used or replaced
+        let mut cache = RateCache::default();
+        cache.insert("USD", "EUR", 0.92, NOW - HOUR);
+        let api = RateApi {
+            on: Some(date!(2025 - 08 - 15)),
+            ..test_api(&server.url())
+        };
+        let conversions = convert_all(
+            &api,
+            &mut cache,
+            "USD",
+            &["EUR".to_string()],
+            Money::from_major(100),
+        )
+        .unwrap();
+
+        assert_eq!(
+            conversions[0].quote,
+            Quote {
+                rate: 0.86,
+                source: RateSource::Historical(date!(2025 - 08 - 15)),
+                age: 0,
+            }
+        );
+        assert_eq!(conversions[0].amount, Money::from_major(86));
+        assert_eq!(
+            cache.get("USD", "EUR").map(|cached| cached.rate),
+            Some(0.92)
+        );
+        eur.assert();
+        assert!(
+            render_conversions("USD", Money::from_major(100), &conversions)
+                .ends_with("EUR                  86.00          0.8600  2025-08-15\n")
+        );
+    }
+
+    #[test]
+    fn test_handle_currency_operations_rejects_future_dates() {
+        let currency_args = lifestuff_types::currency::Currency {
+            operation: None,
+            from: Some("USD".to_string()),
+            amt: Some(Money::from_major(100)),
+            to: vec!["EUR".to_string()],
+            on: Some("01/01/2999".to_string()),
+            endpoint: None,
+            ttl: 12,
+            cache: Some(temp_cache("rejects_future_dates")),
+        };
+        let error = crate::currency::handle_currency_operations(currency_args, false).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "No rates for 2999-01-01 yet, it's in the future"
+        );
+    }
+
+    fn rates(values: &[(Date, f64)]) -> Vec<HistoricalRate> {
+        values
+            .iter()
+            .map(|&(date, rate)| HistoricalRate { date, rate })
+            .collect()
+    }
+
+    #[test]
+    fn test_summarise_and_render_history() {
+        let history = rates(&[
+            (date!(2025 - 08 - 01), 0.85),
+            (date!(2025 - 08 - 02), 0.84),
+            (date!(2025 - 08 - 03), 0.87),
+            (date!(2025 - 08 - 04), 0.84),
+            (date!(2025 - 08 - 05), 0.86),
+        ]);
+
+        assert_eq!(
+            summarise(&history),
+            Some(RateSummary {
+                min: history[1],
+                max: history[2],
+                average: 0.852,
+            })
+        );
+        assert_eq!(summarise(&[]), None);
+        assert_eq!(
+            render_history("EUR", "GBP", &history),
+            "EUR/GBP from 2025-08-01 to 2025-08-05, 5 rates\n\
+             Min             0.8400  2025-08-02\n\
+             Max             0.8700  2025-08-03\n\
+             Average         0.8520\n\
+             ▃▁█▁▆\n"
+        );
+    }
+
+    #[test]
+    fn test_sparkline() {
+        assert_eq!(
+            sparkline(&[1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0], 60),
+            "▁▂▃▄▅▆▇█"
+        );
+        assert_eq!(sparkline(&[1.5, 1.5, 1.5], 60), "▄▄▄");
+        // Longer series are averaged down to the width
+        assert_eq!(sparkline(&[1.0, 1.0, 2.0, 2.0, 3.0, 3.0], 3), "▁▅█");
+        assert_eq!(sparkline(&[1.0; 365], 60).chars().count(), 53);
+        assert_eq!(sparkline(&[], 60), "");
+    }
+
+    #[test]
+    fn test_fetch_history() {
+        let mut server = Server::new();
+        let mock = server
+            .mock("POST", "/currency/history")
+            .match_body(Matcher::PartialJsonString(
+                r#"{"source":"EUR","target":"GBP","from":"2025-08-01","to":"2025-08-03"}"#
+                    .to_string(),
+            ))
+            .with_status(200)
+            .with_body(
+                r#"{"success":{"message":"History","rates":[
+                    {"date":"2025-08-03","rate":0.87},
+                    {"date":"2025-08-01","rate":0.85},
+                    {"date":"2025-08-02","rate":0.84}
+                ]}}"#,
+            )
+            .create();
+
+        let history = fetch_history(
+            &test_api(&server.url()),
+            "EUR",
+            "GBP",
+            date!(2025 - 08 - 01),
+            date!(2025 - 08 - 03),
+        )
+        .unwrap();
+        assert_eq!(
+            history,
+            rates(&[
+                (date!(2025 - 08 - 01), 0.85),
+                (date!(2025 - 08 - 02), 0.84),
+                (date!(2025 - 08 - 03), 0.87),
+            ])
+        );
+        mock.assert();
+    }
+
+    #[test]
+    fn test_fetch_history_errors() {
+        let mut server = Server::new();
+        let _rejected = server
+            .mock("POST", "/currency/history")
+            .match_body(Matcher::PartialJsonString(
+                r#"{"target":"XYZ"}"#.to_string(),
+            ))
+            .with_status(200)
+            .with_body(r#"{"error":{"message":"Unknown currency"}}"#)
+            .create();
+        let _empty = server
+            .mock("POST", "/currency/history")
+            .match_body(Matcher::PartialJsonString(
+                r#"{"target":"GBP"}"#.to_string(),
+            ))
+            .with_status(200)
+            .with_body(r#"{"success":{"message":"History","rates":[]}}"#)
+            .create();
+
+        let api = test_api(&server.url());
+        let from = date!(2025 - 08 - 01);
+        let to = date!(2025 - 08 - 31);
+        assert_eq!(
+            fetch_history(&api, "EUR", "XYZ", from, to)
+                .unwrap_err()
+                .to_string(),
+            "Currency API error for EUR/XYZ: Unknown currency"
+        );
+        assert_eq!(
+            fetch_history(&api, "EUR", "GBP", from, to)
+                .unwrap_err()
+                .to_string(),
+            "No EUR/GBP rates between 2025-08-01 and 2025-08-31"
+        );
+    }
+
+    #[test]
+    fn test_handle_history_validates_arguments() {
+        let api = test_api(DEFAULT_API_HOST);
+        let history = |source: &str, from: &str, to: &str| History {
+            source: source.to_string(),
+            target: "GBP".to_string(),
+            from: Some(from.to_string()),
+            to: Some(to.to_string()),
+        };
+
+        assert!(handle_history(&history("EURO", "01/08/2025", "31/08/2025"), &api).is_err());
+        assert_eq!(
+            handle_history(&history("EUR", "31/08/2025", "01/08/2025"), &api)
+                .unwrap_err()
+                .to_string(),
+            "The history must start before it ends, got 2025-08-31 to 2025-08-01"
+        );
+        assert_eq!(
+            handle_history(&history("EUR", "01/08/2999", "31/08/2999"), &api)
+                .unwrap_err()
+                .to_string(),
+            "No rates for 2999-08-31 yet, it's in the future"
+        );
+    }
 }
@@ -1,7 +1,12 @@
 mod add;
+mod age;
+mod cal;
 mod common;
 mod datetimekeeper;
+mod holidays;
+mod hours;
 mod ordinal;
+mod range;
 pub use common::*;
 pub use datetimekeeper::*;
 mod diff;
@@ -12,7 +17,11 @@ pub fn handle_date_operations(date_args: DateOperations, verbose: bool) -> Resul
     match &date_args.operation_type {
         DateOption::Diff(diff_args) => diff::do_diff_date(diff_args, verbose),
         DateOption::Add(add_args) => add::do_add_date(add_args, verbose),
+        DateOption::Age(age_args) => age::do_age(age_args, verbose),
+        DateOption::Cal(cal_args) => cal::do_calendar(cal_args, verbose),
+        DateOption::Hours(hours_args) => hours::do_timesheet(hours_args, verbose),
         DateOption::Ordinal => ordinal::handle_ordinal_operations(),
+        DateOption::Range(range_args) => range::do_date_range(range_args, verbose),
     }
 }
 
@@ -0,0 +1,168 @@
+use crate::dateinfo::{self, DateTimeKeeper};
+use anyhow::{Result, ensure};
+use lifestuff_types::dateinfo::age::Age;
+use time::{Month, Time};
+
+#[derive(Debug)]
+struct AgeBreakdown {
+    years: i32,
+    months: i32,
+    days: i64,
+    next_birthday: DateTimeKeeper,
+    days_until_birthday: i64,
+    next_age: i32,
+}
+
+fn plural(value: i64, unit: &str) -> String {
+    if value == 1 {
+        format!("{value} {unit}")
+    } else {
+        format!("{value} {unit}s")
+    }
+}
+
+fn calculate_age(birth_date: &DateTimeKeeper, on_date: &DateTimeKeeper) -> Result<AgeBreakdown> {
+    // Ages are counted in whole days, so any time of day given with either date is dropped
+    let (mut birth_date, mut on_date) = (*birth_date, *on_date);
+    birth_date.set_time(&Time::MIDNIGHT);
+    on_date.set_time(&Time::MIDNIGHT);
+
+    ensure!(
+        birth_date <= on_date,
+        "Birth date {:?} is after {:?}",
+        birth_date,
+        on_date
+    );
+
+    let mut total_months = (on_date.date().year() - birth_date.date().year()) * 12
+        + (u8::from(on_date.date().month()) as i32 - u8::from(birth_date.date().month()) as i32);
+    if birth_date.apply_month_delta(total_months)? > on_date {
+        total_months -= 1;
+    }
+    let last_monthiversary = birth_date.apply_month_delta(total_months)?;
+
+    let mut next_age = on_date.date().year() - birth_date.date().year();
+    let mut next_birthday = birth_date.apply_year_delta(next_age)?;
+    if next_birthday < on_date {
+        next_age += 1;
+        next_birthday = birth_date.apply_year_delta(next_age)?;
+    }
+
+    Ok(AgeBreakdown {
+        years: total_months / 12,
+        months: total_months % 12,
+        days: (on_date - last_monthiversary).whole_days(),
+        next_birthday,
+        days_until_birthday: (next_birthday - on_date).whole_days(),
+        next_age,
+    })
+}
+
+pub fn do_age(age_args: &Age, verbose: bool) -> Result<()> {
+    if verbose {
+        println!("Args were: {:?}", age_args)
+    }
+
+    let birth_date = dateinfo::get_date_from_string_arg(Some(&age_args.birthdate), verbose)?;
+    let on_date = dateinfo::get_date_from_string_arg(age_args.on.as_deref(), verbose)?;
+
+    let age = calculate_age(&birth_date, &on_date)?;
+    if verbose {
+        println!("Age breakdown: {:?}", age);
+    }
+
+    println!(
+        "Age on {:?} is {}, {} and {}",
+        on_date.date().to_calendar_date(),
+        plural(age.years.into(), "year"),
+        plural(age.months.into(), "month"),
+        plural(age.days, "day")
+    );
+
+    if age.days_until_birthday == 0 {
+        println!("Happy birthday! Turning {} today", age.next_age);
+    } else {
+        println!(
+            "Next birthday is {:?}, a {}, turning {} in {}",
+            age.next_birthday,
+            age.next_birthday.date().weekday(),
+            age.next_age,
+            plural(age.days_until_birthday, "day")
+        );
+    }
+
+    let birth = birth_date.date();
+    if birth.month() == Month::February && birth.day() == 29 && age.next_birthday.date().day() == 28
+    {
+        println!("29 February birthday is observed on 28 February in non-leap years");
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::macros::date;
+
+    fn keeper(input: &str) -> DateTimeKeeper {
+        DateTimeKeeper::new_from_dmy_str(input, false).unwrap()
+    }
+
+    #[test]
+    fn test_calculate_age_exact_breakdown() {
+        let age = calculate_age(&keeper("15/06/1990"), &keeper("20/03/2025")).unwrap();
+        assert_eq!((age.years, age.months, age.days), (34, 9, 5));
+        assert_eq!(age.next_birthday.date(), date!(2025 - 06 - 15));
+        assert_eq!(age.days_until_birthday, 87);
+        assert_eq!(age.next_age, 35);
+    }
+
+    #[test]
+    fn test_calculate_age_on_birthday() {
+        let age = calculate_age(&keeper("15/06/1990"), &keeper("15/06/2025")).unwrap();
+        assert_eq!((age.years, age.months, age.days), (35, 0, 0));
+        assert_eq!(age.days_until_birthday, 0);
+        assert_eq!(age.next_age, 35);
+    }
+
+    #[test]
+    fn test_calculate_age_leap_day_birthday() {
+        let age = calculate_age(&keeper("29/02/2000"), &keeper("01/01/2025")).unwrap();
+        assert_eq!(age.years, 24);
+        assert_eq!(age.next_birthday.date(), date!(2025 - 02 - 28));
+
+        let age = calculate_age(&keeper("29/02/2000"), &keeper("01/01/2028")).unwrap();
+        assert_eq!(age.next_birthday.date(), date!(2028 - 02 - 29));
+    }
+
+    #[test]
+    fn test_calculate_age_ignores_the_time_of_day() {
+        let born_at_noon = keeper("01/01/2000").with_time_str("12:00", false).unwrap();
+        let age = calculate_age(&born_at_noon, &keeper("01/01/2025")).unwrap();
+        assert_eq!((age.years, age.months, age.days), (25, 0, 0));
+        assert_eq!(age.days_until_birthday, 0);
+        assert_eq!(age.next_age, 25);
+
+        let just_after_midnight = keeper("01/01/2025")
+            .with_time_str("00:00:01", false)
+            .unwrap();
+        let age = calculate_age(&keeper("01/01/2000"), &just_after_midnight).unwrap();
+        assert_eq!(age.days_until_birthday, 0);
+        assert_eq!(age.next_birthday.date(), date!(2025 - 01 - 01));
+    }
+
+    #[test]
+    fn test_calculate_age_birth_after_date_fails() {
+        assert!(calculate_age(&keeper("01/01/2030"), &keeper("01/01/2025")).is_err());
+    }
+
+    #[test]
+    fn test_do_age() {
+        let age_args = Age {
+            birthdate: "29/02/2000".to_string(),
+            on: Some("01/01/2025".to_string()),
+        };
+        assert!(do_age(&age_args, false).is_ok());
+    }
+}
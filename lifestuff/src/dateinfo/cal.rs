@@ -0,0 +1,264 @@
+use crate::dateinfo::{self, DateTimeKeeper, holidays};
+use anyhow::{Context, Result, ensure};
+use colored::Colorize;
+use lifestuff_types::dateinfo::cal::Cal;
+use time::{Date, Month, Weekday};
+
+const CALENDAR_WIDTH: usize = 24;
+
+/// Lays out the days of a month into rows of 7, padding either side with `None`
+fn month_weeks(year: i32, month: Month, week_start: Weekday) -> Result<Vec<[Option<Date>; 7]>> {
+    let mut weeks = Vec::new();
+    let mut week = [None; 7];
+    let last_day = time::util::days_in_month(month, year);
+
+    for day in 1..=last_day {
+        let current = Date::from_calendar_date(year, month, day)?;
+        let column = (current.weekday().number_days_from_monday() as usize + 7
+            - week_start.number_days_from_monday() as usize)
+            % 7;
+        week[column] = Some(current);
+
+        if column == 6 || day == last_day {
+            weeks.push(week);
+            week = [None; 7];
+        }
+    }
+
+    Ok(weeks)
+}
+
+/// ISO week number of the Monday within the given calendar row
+fn week_number(week: &[Option<Date>; 7], week_start: Weekday) -> Result<u8> {
+    // Every day from the Monday on is in its ISO week. A row holding only the Sunday before
+    // it takes the week from the day after.
+    let monday_column = (7 - week_start.number_days_from_monday() as usize) % 7;
+    let date = match week[monday_column..].iter().flatten().next() {
+        Some(date) => *date,
+        None => week
+            .iter()
+            .flatten()
+            .next()
+            .and_then(|date| date.next_day())
+            .context("Unable to find the Monday of a calendar week")?,
+    };
+
+    Ok(date.iso_week())
+}
+
+fn render_month(
+    year: i32,
+    month: Month,
+    week_start: Weekday,
+    today: &Date,
+    marked: &[Date],
+) -> Result<Vec<String>> {
+    let bank_holidays = holidays::bank_holidays(year)?;
+    let title = format!("{:^CALENDAR_WIDTH$}", format!("{month} {year}"));
+    let mut lines = vec![title.trim_end().to_string()];
+
+    let mut header = String::from(" Wk");
+    let mut weekday = week_start;
+    for _ in 0..7 {
+        header.push_str(&format!(" {}", &weekday.to_string()[..2]));
+        weekday = weekday.next();
+    }
+    lines.push(header.bold().to_string());
+
+    for week in month_weeks(year, month, week_start)? {
+        let mut line = format!("{:>3}", week_number(&week, week_start)?)
+            .dimmed()
+            .to_string();
+        for day in week {
+            line.push(' ');
+            let Some(day) = day else {
+                line.push_str("  ");
+                continue;
+            };
+
+            let mut cell = format!("{:>2}", day.day()).normal();
+            if bank_holidays.contains(&day) {
+                cell = cell.red();
+            }
+            if marked.contains(&day) {
+                cell = cell.yellow().bold().underline();
+            }
+            if day == *today {
+                cell = cell.reversed();
+            }
+            line.push_str(&cell.to_string());
+        }
+        lines.push(line);
+    }
+
+    Ok(lines)
+}
+
+pub fn do_calendar(cal_args: &Cal, verbose: bool) -> Result<()> {
+    if verbose {
+        println!("Args were: {:?}", cal_args)
+    }
+
+    let today = DateTimeKeeper::now().date();
+    let year = cal_args.year.unwrap_or(today.year());
+    ensure!(
+        (Date::MIN.year()..=Date::MAX.year()).contains(&year),
+        "Invalid year passed {}. Must be between {} and {}",
+        year,
+        Date::MIN.year(),
+        Date::MAX.year()
+    );
+    let month = cal_args.month.unwrap_or(u8::from(today.month()));
+    ensure!(
+        (1..=12).contains(&month),
+        "Invalid month passed {}. Must be between 1 and 12",
+        month
+    );
+
+    let week_start = if cal_args.sunday_start {
+        Weekday::Sunday
+    } else {
+        Weekday::Monday
+    };
+
+    let marked = cal_args
+        .mark
+        .iter()
+        .map(|mark| Ok(dateinfo::get_date_from_string_arg(Some(mark), verbose)?.date()))
+        .collect::<Result<Vec<Date>>>()?;
+
+    let months = if cal_args.full_year {
+        (1..=12).collect::<Vec<u8>>()
+    } else {
+        vec![month]
+    };
+
+    for month in months {
+        for line in render_month(year, Month::try_from(month)?, week_start, &today, &marked)? {
+            println!("{line}");
+        }
+        println!();
+    }
+
+    println!(
+        "{} {} {}",
+        "today".reversed(),
+        "bank holiday".red(),
+        "marked".yellow().bold().underline()
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::macros::date;
+
+    #[test]
+    fn test_month_weeks_monday_start() {
+        // March 2026 starts on a Sunday and ends on a Tuesday
+        let weeks = month_weeks(2026, Month::March, Weekday::Monday).unwrap();
+        assert_eq!(weeks.len(), 6);
+        assert_eq!(weeks[0][6], Some(date!(2026 - 03 - 01)));
+        assert!(weeks[0][..6].iter().all(Option::is_none));
+        assert_eq!(weeks[5][1], Some(date!(2026 - 03 - 31)));
+    }
+
+    #[test]
+    fn test_month_weeks_sunday_start() {
+        let weeks = month_weeks(2026, Month::March, Weekday::Sunday).unwrap();
+        assert_eq!(weeks.len(), 5);
+        assert_eq!(weeks[0][0], Some(date!(2026 - 03 - 01)));
+        assert_eq!(weeks[4][2], Some(date!(2026 - 03 - 31)));
+    }
+
+    #[test]
+    fn test_week_number_uses_iso_week() {
+        let weeks = month_weeks(2026, Month::January, Weekday::Monday).unwrap();
+        // 1 January 2026 is a Thursday in ISO week 1
+        assert_eq!(week_number(&weeks[0], Weekday::Monday).unwrap(), 1);
+
+        let weeks = month_weeks(2021, Month::January, Weekday::Monday).unwrap();
+        // 1 January 2021 is a Friday belonging to week 53 of 2020
+        assert_eq!(week_number(&weeks[0], Weekday::Monday).unwrap(), 53);
+
+        let weeks = month_weeks(2026, Month::March, Weekday::Sunday).unwrap();
+        assert_eq!(week_number(&weeks[0], Weekday::Sunday).unwrap(), 10);
+    }
+
+    #[test]
+    fn test_calendar_at_the_ends_of_the_date_range() {
+        let weeks = month_weeks(9999, Month::December, Weekday::Sunday).unwrap();
+        assert_eq!(weeks.last().unwrap()[5], Some(date!(9999 - 12 - 31)));
+        assert_eq!(
+            week_number(weeks.last().unwrap(), Weekday::Sunday).unwrap(),
+            52
+        );
+
+        // 1 January of year 0 is a Saturday in the last ISO week of the year before
+        let weeks = month_weeks(0, Month::January, Weekday::Monday).unwrap();
+        assert_eq!(week_number(&weeks[0], Weekday::Monday).unwrap(), 52);
+
+        assert!(
+            render_month(
+                -9999,
+                Month::January,
+                Weekday::Sunday,
+                &date!(2026 - 03 - 10),
+                &[]
+            )
+            .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_render_month_line_count() {
+        let lines = render_month(
+            2026,
+            Month::March,
+            Weekday::Monday,
+            &date!(2026 - 03 - 10),
+            &[date!(2026 - 03 - 20)],
+        )
+        .unwrap();
+        assert_eq!(lines.len(), 8);
+        assert!(lines[0].contains("March 2026"));
+    }
+
+    #[test]
+    fn test_do_calendar() {
+        let cal_args = Cal {
+            month: Some(12),
+            year: Some(2025),
+            mark: vec!["24/12/2025".to_string()],
+            full_year: false,
+            sunday_start: false,
+        };
+        assert!(do_calendar(&cal_args, false).is_ok());
+    }
+
+    #[test]
+    fn test_do_calendar_invalid_month() {
+        let cal_args = Cal {
+            month: Some(13),
+            year: Some(2025),
+            mark: vec![],
+            full_year: false,
+            sunday_start: false,
+        };
+        assert!(do_calendar(&cal_args, false).is_err());
+    }
+
+    #[test]
+    fn test_do_calendar_year_out_of_range() {
+        let cal_args = Cal {
+            month: Some(1),
+            year: Some(10000),
+            mark: vec![],
+            full_year: false,
+            sunday_start: false,
+        };
+        assert!(do_calendar(&cal_args, false).is_err());
+    }
+}
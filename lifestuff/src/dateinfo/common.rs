@@ -1,14 +1,76 @@
 use crate::dateinfo::DateTimeKeeper;
 use anyhow::Result;
 
+/// Splits an input such as `dd/mm/yyyy HH:MM` or `yyyymmddTHHMM` into its date and time parts
+fn split_date_and_time(input: &str) -> (&str, Option<&str>) {
+    if let Some((date, time)) = input.split_once(['T', 't']) {
+        return (date.trim(), Some(time.trim()));
+    }
+
+    if input.contains(':')
+        && let Some((date, time)) = input.trim().rsplit_once(char::is_whitespace)
+    {
+        return (date.trim(), Some(time.trim()));
+    }
+
+    (input.trim(), None)
+}
+
 pub fn get_date_from_string_arg(input_date: Option<&str>, verbose: bool) -> Result<DateTimeKeeper> {
     if let Some(input_date_str) = input_date {
-        if input_date_str.contains(['/', '-']) {
-            DateTimeKeeper::new_from_dmy_str(input_date_str, verbose)
+        let (date_str, time_str) = split_date_and_time(input_date_str);
+
+        let date = if date_str.contains(['/', '-']) {
+            DateTimeKeeper::new_from_dmy_str(date_str, verbose)?
         } else {
-            DateTimeKeeper::new_from_yyyymmdd_str(input_date_str, verbose)
+            DateTimeKeeper::new_from_yyyymmdd_str(date_str, verbose)?
+        };
+
+        match time_str {
+            Some(time_str) => date.with_time_str(time_str, verbose),
+            None => Ok(date),
         }
     } else {
         Ok(DateTimeKeeper::new_at_midnight())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::macros::{date, time};
+
+    #[test]
+    fn test_get_date_from_string_arg_dmy_with_time() {
+        let result = get_date_from_string_arg(Some("01/03/2023 09:30"), false).unwrap();
+        assert_eq!(result.date(), date!(2023 - 03 - 01));
+        assert_eq!(result.time(), time!(09:30:00));
+    }
+
+    #[test]
+    fn test_get_date_from_string_arg_dmy_with_seconds() {
+        let result = get_date_from_string_arg(Some("31 / 1/ 2023 23:59:58"), false).unwrap();
+        assert_eq!(result.date(), date!(2023 - 01 - 31));
+        assert_eq!(result.time(), time!(23:59:58));
+    }
+
+    #[test]
+    fn test_get_date_from_string_arg_yyyymmdd_with_time() {
+        let result = get_date_from_string_arg(Some("20230301T0930"), false).unwrap();
+        assert_eq!(result.date(), date!(2023 - 03 - 01));
+        assert_eq!(result.time(), time!(09:30:00));
+    }
+
+    #[test]
+    fn test_get_date_from_string_arg_without_time_is_midnight() {
+        let result = get_date_from_string_arg(Some("01/03/2023"), false).unwrap();
+        assert_eq!(result.time(), time!(00:00:00));
+    }
+
+    #[test]
+    fn test_get_date_from_string_arg_invalid_time_fails() {
+        assert!(get_date_from_string_arg(Some("01/03/2023 24:00"), false).is_err());
+        assert!(get_date_from_string_arg(Some("20230301T09"), false).is_err());
+        assert!(get_date_from_string_arg(Some("01/03/2023 09:61"), false).is_err());
+    }
+}
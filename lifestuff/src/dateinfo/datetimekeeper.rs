@@ -5,7 +5,7 @@ use time::macros::format_description;
 use time::util::days_in_year;
 use time::{Date, Duration, Month, OffsetDateTime, PrimitiveDateTime, Time};
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub struct DateTimeKeeper {
     utc_date_time: OffsetDateTime,
 }
@@ -90,6 +90,65 @@ fn parse_input_date_dmy(input: &str, verbose: bool) -> Result<(u32, u8, u8), Err
     Ok((if year < 100 { year + 2000 } else { year }, month, date))
 }
 
+fn parse_input_time_hms(input: &str, verbose: bool) -> Result<(u8, u8, u8), Error> {
+    let input = input.trim();
+    let tokens = if input.contains(':') {
+        input
+            .split(':')
+            .map(|token| token.trim())
+            .collect::<Vec<&str>>()
+    } else {
+        ensure!(
+            input.len() == 4 || input.len() == 6,
+            "Error handling time parsing. Expected `HH:MM[:SS]` or `HHMM[SS]` but found '{input}'"
+        );
+        (0..input.len())
+            .step_by(2)
+            .map(|idx| input.get(idx..idx + 2).unwrap_or_default())
+            .collect::<Vec<&str>>()
+    };
+
+    if verbose {
+        println!("The time tokens were {:?}", tokens);
+    }
+
+    ensure!(
+        tokens.len() == 2 || tokens.len() == 3,
+        "Error handling time parsing. Expected hours, minutes and optional seconds"
+    );
+
+    let hour = tokens[0].parse::<u8>().context(format!(
+        "Error handling hour parsing. Could not convert '{}' to a number",
+        tokens[0]
+    ))?;
+    let minute = tokens[1].parse::<u8>().context(format!(
+        "Error handling minute parsing. Could not convert '{}' to a number",
+        tokens[1]
+    ))?;
+    let second = match tokens.get(2) {
+        Some(token) => token.parse::<u8>().context(format!(
+            "Error handling second parsing. Could not convert '{}' to a number",
+            token
+        ))?,
+        None => 0,
+    };
+
+    ensure!(
+        hour < 24,
+        "Invalid hour passed {hour}. Must be between 0 and 23"
+    );
+    ensure!(
+        minute < 60,
+        "Invalid minute passed {minute}. Must be between 0 and 59"
+    );
+    ensure!(
+        second < 60,
+        "Invalid second passed {second}. Must be between 0 and 59"
+    );
+
+    Ok((hour, minute, second))
+}
+
 fn get_last_day_of_proposed_month(year: i32, month: time::Month) -> u8 {
     time::util::days_in_month(month, year)
 }
@@ -132,6 +191,17 @@ impl DateTimeKeeper {
         let parsed_date = parse_input_date_yyyymmdd(input, verbose)?;
         Self::new_from_dmy(parsed_date.2, parsed_date.1, parsed_date.0)
     }
+
+    /// Applies a `HH:MM[:SS]` or `HHMM[SS]` time of day to an already parsed date
+    pub fn with_time_str(&self, input: &str, verbose: bool) -> Result<Self> {
+        let (hour, minute, second) = parse_input_time_hms(input, verbose)?;
+
+        Ok(Self {
+            utc_date_time: self
+                .utc_date_time
+                .replace_time(Time::from_hms(hour, minute, second)?),
+        })
+    }
 }
 
 #[doc = r"Accessors"]
@@ -27,6 +27,8 @@ pub fn do_diff_date(diff_args: &Diff, verbose: bool) -> Result<()> {
         let output = match option {
             DateDuration::Days => do_output_format(date_diff.whole_days(), &duration_type),
             DateDuration::Hours => do_output_format(date_diff.whole_hours(), &duration_type),
+            DateDuration::Minutes => do_output_format(date_diff.whole_minutes(), &duration_type),
+            DateDuration::Seconds => do_output_format(date_diff.whole_seconds(), &duration_type),
             DateDuration::Weeks => do_output_format(date_diff.whole_weeks(), &duration_type),
             DateDuration::Years => do_output_format(date_diff.whole_days() / 365, &duration_type),
         };
@@ -109,6 +111,21 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn test_do_diff_date_with_times() {
+        let diff_args = Diff {
+            date1: "01/01/2023 08:15".to_string(),
+            date2: Some("01/01/2023 17:45:30".to_string()),
+            to: vec![
+                DateDuration::Hours,
+                DateDuration::Minutes,
+                DateDuration::Seconds,
+            ],
+        };
+        let result = do_diff_date(&diff_args, false);
+        assert!(result.is_ok());
+    }
+
     #[test]
     fn test_do_diff_date_verbose() {
         let diff_args = Diff {
@@ -0,0 +1,128 @@
+use anyhow::Result;
+use time::{Date, Month, Weekday};
+
+/// Easter Sunday for the given year (anonymous Gregorian algorithm)
+fn easter_sunday(year: i32) -> Result<Date> {
+    let a = year % 19;
+    let b = year / 100;
+    let c = year % 100;
+    let d = b / 4;
+    let e = b % 4;
+    let f = (b + 8) / 25;
+    let g = (b - f + 1) / 3;
+    let h = (19 * a + b - d - g + 15) % 30;
+    let i = c / 4;
+    let k = c % 4;
+    let l = (32 + 2 * e + 2 * i - h - k) % 7;
+    let m = (a + 11 * h + 22 * l) / 451;
+    let month = (h + l - 7 * m + 114) / 31;
+    let day = (h + l - 7 * m + 114) % 31 + 1;
+
+    Ok(Date::from_calendar_date(
+        year,
+        Month::try_from(month as u8)?,
+        day as u8,
+    )?)
+}
+
+fn first_weekday_of_month(year: i32, month: Month, weekday: Weekday) -> Result<Date> {
+    let first = Date::from_calendar_date(year, month, 1)?;
+    let offset =
+        (7 + weekday.number_days_from_monday() - first.weekday().number_days_from_monday()) % 7;
+    Ok(Date::from_calendar_date(year, month, 1 + offset)?)
+}
+
+fn last_weekday_of_month(year: i32, month: Month, weekday: Weekday) -> Result<Date> {
+    let last_day = time::util::days_in_month(month, year);
+    let last = Date::from_calendar_date(year, month, last_day)?;
+    let offset =
+        (7 + last.weekday().number_days_from_monday() - weekday.number_days_from_monday()) % 7;
+    Ok(Date::from_calendar_date(year, month, last_day - offset)?)
+}
+
+/// Moves a holiday falling on a weekend to the following Monday
+fn substitute_weekend(date: Date) -> Date {
+    match date.weekday() {
+        Weekday::Saturday => date + time::Duration::days(2),
+        Weekday::Sunday => date + time::Duration::days(1),
+        _ => date,
+    }
+}
+
+/// England and Wales bank holidays for the given year, including substitute days.
+/// One-off holidays (coronations, jubilees, etc.) are not included.
+pub fn bank_holidays(year: i32) -> Result<Vec<Date>> {
+    let easter = easter_sunday(year)?;
+    let christmas = Date::from_calendar_date(year, Month::December, 25)?;
+
+    let (christmas_day, boxing_day) = match christmas.weekday() {
+        Weekday::Friday => (christmas, christmas + time::Duration::days(3)),
+        Weekday::Saturday => (
+            christmas + time::Duration::days(2),
+            christmas + time::Duration::days(3),
+        ),
+        Weekday::Sunday => (
+            christmas + time::Duration::days(2),
+            christmas + time::Duration::days(1),
+        ),
+        _ => (christmas, christmas + time::Duration::days(1)),
+    };
+
+    let mut holidays = vec![
+        substitute_weekend(Date::from_calendar_date(year, Month::January, 1)?),
+        easter - time::Duration::days(2),
+        easter + time::Duration::days(1),
+        first_weekday_of_month(year, Month::May, Weekday::Monday)?,
+        last_weekday_of_month(year, Month::May, Weekday::Monday)?,
+        last_weekday_of_month(year, Month::August, Weekday::Monday)?,
+        christmas_day,
+        boxing_day,
+    ];
+    holidays.sort();
+
+    Ok(holidays)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::macros::date;
+
+    #[test]
+    fn test_easter_sunday() {
+        assert_eq!(easter_sunday(2024).unwrap(), date!(2024 - 03 - 31));
+        assert_eq!(easter_sunday(2025).unwrap(), date!(2025 - 04 - 20));
+        assert_eq!(easter_sunday(2026).unwrap(), date!(2026 - 04 - 05));
+    }
+
+    #[test]
+    fn test_bank_holidays_2025() {
+        assert_eq!(
+            bank_holidays(2025).unwrap(),
+            vec![
+                date!(2025 - 01 - 01),
+                date!(2025 - 04 - 18),
+                date!(2025 - 04 - 21),
+                date!(2025 - 05 - 05),
+                date!(2025 - 05 - 26),
+                date!(2025 - 08 - 25),
+                date!(2025 - 12 - 25),
+                date!(2025 - 12 - 26),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_bank_holidays_weekend_substitutes() {
+        // Christmas 2021 was a Saturday, Boxing Day a Sunday
+        let holidays_2021 = bank_holidays(2021).unwrap();
+        assert!(holidays_2021.contains(&date!(2021 - 12 - 27)));
+        assert!(holidays_2021.contains(&date!(2021 - 12 - 28)));
+        assert!(!holidays_2021.contains(&date!(2021 - 12 - 25)));
+
+        // New Year's Day 2022 was a Saturday
+        let holidays_2022 = bank_holidays(2022).unwrap();
+        assert!(holidays_2022.contains(&date!(2022 - 01 - 03)));
+        assert!(!holidays_2022.contains(&date!(2022 - 01 - 01)));
+    }
+}
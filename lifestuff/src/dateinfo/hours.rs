@@ -0,0 +1,340 @@
+use crate::csv_utils;
+use crate::dateinfo::{self, DateTimeKeeper};
+use anyhow::{Context, Result, ensure};
+use lifestuff_types::dateinfo::hours::Hours;
+use std::collections::BTreeMap;
+use std::fs;
+use time::{Date, Duration};
+
+#[derive(Debug, Clone, Copy)]
+struct Shift {
+    clock_in: DateTimeKeeper,
+    clock_out: DateTimeKeeper,
+    break_time: Duration,
+}
+
+#[derive(Debug, PartialEq)]
+struct WeekSummary {
+    year: i32,
+    week: u8,
+    worked: Duration,
+    overtime: Duration,
+}
+
+impl Shift {
+    fn new(
+        clock_in: DateTimeKeeper,
+        clock_out: DateTimeKeeper,
+        break_time: Duration,
+    ) -> Result<Self> {
+        ensure!(
+            clock_out > clock_in,
+            "Clock-out {:?} must be after clock-in {:?}",
+            clock_out,
+            clock_in
+        );
+        ensure!(
+            break_time < clock_out - clock_in,
+            "Break of {} is longer than the shift starting {:?}",
+            format_duration(break_time),
+            clock_in
+        );
+
+        Ok(Self {
+            clock_in,
+            clock_out,
+            break_time,
+        })
+    }
+
+    fn worked(&self) -> Duration {
+        self.clock_out - self.clock_in - self.break_time
+    }
+}
+
+fn format_duration(duration: Duration) -> String {
+    format!(
+        "{}h {:02}m",
+        duration.whole_hours(),
+        duration.whole_minutes() % 60
+    )
+}
+
+fn hours_to_duration(hours: f64) -> Result<Duration> {
+    ensure!(
+        hours > 0.0,
+        "Overtime threshold must be a positive number of hours, got: {hours}"
+    );
+    Duration::checked_seconds_f64(hours * 3600.0)
+        .with_context(|| format!("Overtime threshold of {hours:e} hours is too large"))
+}
+
+fn parse_shift(
+    clock_in: &str,
+    clock_out: &str,
+    break_minutes: u32,
+    verbose: bool,
+) -> Result<Shift> {
+    Shift::new(
+        dateinfo::get_date_from_string_arg(Some(clock_in), verbose)?,
+        dateinfo::get_date_from_string_arg(Some(clock_out), verbose)?,
+        Duration::minutes(break_minutes.into()),
+    )
+}
+
+fn parse_shifts_csv(contents: &str, default_break: u32, verbose: bool) -> Result<Vec<Shift>> {
+    csv_utils::parse_rows(contents, |line| {
+        let fields = line.split(',').map(str::trim).collect::<Vec<&str>>();
+        ensure!(
+            fields.len() == 2 || fields.len() == 3,
+            "expected `clock_in,clock_out[,break_minutes]` but found '{line}'"
+        );
+        let break_minutes = match fields.get(2) {
+            Some(field) if !field.is_empty() => field
+                .parse::<u32>()
+                .with_context(|| format!("unable to parse break minutes '{field}'"))?,
+            _ => default_break,
+        };
+
+        parse_shift(fields[0], fields[1], break_minutes, verbose).context("invalid shift")
+    })
+}
+
+fn summarise_weeks(
+    shifts: &[Shift],
+    daily_threshold: Option<Duration>,
+    weekly_threshold: Option<Duration>,
+) -> Vec<WeekSummary> {
+    let mut days: BTreeMap<Date, Duration> = BTreeMap::new();
+    for shift in shifts {
+        *days.entry(shift.clock_in.date()).or_default() += shift.worked();
+    }
+
+    let mut weeks: BTreeMap<(i32, u8), (Duration, Duration)> = BTreeMap::new();
+    for (date, worked) in days {
+        let daily_overtime = daily_threshold
+            .map(|threshold| (worked - threshold).max(Duration::ZERO))
+            .unwrap_or_default();
+        let (week_worked, week_overtime) = weeks
+            .entry((date.to_iso_week_date().0, date.iso_week()))
+            .or_default();
+        *week_worked += worked;
+        *week_overtime += daily_overtime;
+    }
+
+    weeks
+        .into_iter()
+        .map(|((year, week), (worked, daily_overtime))| {
+            let weekly_overtime = weekly_threshold
+                .map(|threshold| (worked - daily_overtime - threshold).max(Duration::ZERO))
+                .unwrap_or_default();
+            WeekSummary {
+                year,
+                week,
+                worked,
+                overtime: daily_overtime + weekly_overtime,
+            }
+        })
+        .collect()
+}
+
+pub fn do_timesheet(hours_args: &Hours, verbose: bool) -> Result<()> {
+    if verbose {
+        println!("Args were: {:?}", hours_args)
+    }
+
+    ensure!(
+        hours_args.times.len().is_multiple_of(2),
+        "Clock-in and clock-out times must be given in pairs, got {} times",
+        hours_args.times.len()
+    );
+
+    let mut shifts = hours_args
+        .times
+        .chunks(2)
+        .map(|pair| parse_shift(&pair[0], &pair[1], hours_args.break_minutes, verbose))
+        .collect::<Result<Vec<Shift>>>()?;
+
+    if let Some(path) = &hours_args.file {
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("Unable to read timesheet file {}", path.display()))?;
+        shifts.extend(parse_shifts_csv(
+            &contents,
+            hours_args.break_minutes,
+            verbose,
+        )?);
+    }
+
+    ensure!(
+        !shifts.is_empty(),
+        "No shifts passed. Provide clock-in/clock-out times or a --file"
+    );
+
+    if verbose {
+        for shift in &shifts {
+            println!(
+                "Shift {:?} -> {:?} worked {}",
+                shift.clock_in,
+                shift.clock_out,
+                format_duration(shift.worked())
+            );
+        }
+    }
+
+    let weeks = summarise_weeks(
+        &shifts,
+        hours_args
+            .daily_overtime
+            .map(hours_to_duration)
+            .transpose()?,
+        hours_args
+            .weekly_overtime
+            .map(hours_to_duration)
+            .transpose()?,
+    );
+
+    let mut total_worked = Duration::ZERO;
+    let mut total_overtime = Duration::ZERO;
+    for week in &weeks {
+        println!(
+            "Week {:>2} of {}: {} worked, {} overtime",
+            week.week,
+            week.year,
+            format_duration(week.worked),
+            format_duration(week.overtime)
+        );
+        total_worked += week.worked;
+        total_overtime += week.overtime;
+    }
+    println!(
+        "Total: {} worked across {} shifts, {} overtime",
+        format_duration(total_worked),
+        shifts.len(),
+        format_duration(total_overtime)
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shift(clock_in: &str, clock_out: &str, break_minutes: u32) -> Shift {
+        parse_shift(clock_in, clock_out, break_minutes, false).unwrap()
+    }
+
+    #[test]
+    fn test_shift_worked_subtracts_break() {
+        let shift = shift("03/03/2025 09:00", "03/03/2025 17:30", 30);
+        assert_eq!(shift.worked(), Duration::hours(8));
+    }
+
+    #[test]
+    fn test_shift_overnight() {
+        let shift = shift("03/03/2025 22:00", "04/03/2025 06:15", 0);
+        assert_eq!(shift.worked(), Duration::minutes(495));
+    }
+
+    #[test]
+    fn test_shift_clock_out_before_clock_in_fails() {
+        assert!(parse_shift("03/03/2025 17:00", "03/03/2025 09:00", 0, false).is_err());
+        assert!(parse_shift("03/03/2025 09:00", "03/03/2025 09:20", 30, false).is_err());
+    }
+
+    #[test]
+    fn test_parse_shifts_csv() {
+        let contents = "clock_in,clock_out,break\n\
+                        03/03/2025 09:00,03/03/2025 17:00,60\n\
+                        # a comment\n\
+                        20250304T0900,20250304T1700\n";
+        let shifts = parse_shifts_csv(contents, 30, false).unwrap();
+        assert_eq!(shifts.len(), 2);
+        assert_eq!(shifts[0].worked(), Duration::hours(7));
+        assert_eq!(shifts[1].worked(), Duration::minutes(450));
+    }
+
+    #[test]
+    fn test_parse_shifts_csv_bad_row_fails() {
+        assert!(parse_shifts_csv("03/03/2025 09:00\n", 0, false).is_err());
+        let error = parse_shifts_csv(
+            "03/03/2025 09:00,03/03/2025 17:00\nMarch 4,03/04/2025 17:00\n",
+            0,
+            false,
+        )
+        .unwrap_err();
+        assert!(error.to_string().starts_with("Line 2: invalid shift"));
+    }
+
+    #[test]
+    fn test_summarise_weeks_overtime() {
+        // Monday to Friday of ISO week 10, 9 hours a day, then a Monday in week 11
+        let mut shifts = (3..=7)
+            .map(|day| {
+                shift(
+                    &format!("{day:02}/03/2025 08:00"),
+                    &format!("{day:02}/03/2025 17:00"),
+                    0,
+                )
+            })
+            .collect::<Vec<Shift>>();
+        shifts.push(shift("10/03/2025 09:00", "10/03/2025 13:00", 0));
+
+        let weeks = summarise_weeks(&shifts, Some(Duration::hours(8)), Some(Duration::hours(37)));
+        assert_eq!(
+            weeks,
+            vec![
+                WeekSummary {
+                    year: 2025,
+                    week: 10,
+                    worked: Duration::hours(45),
+                    // 5 hours over the daily limit, then 3 more over the weekly limit
+                    overtime: Duration::hours(8),
+                },
+                WeekSummary {
+                    year: 2025,
+                    week: 11,
+                    worked: Duration::hours(4),
+                    overtime: Duration::ZERO,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_hours_to_duration() {
+        assert_eq!(hours_to_duration(7.5).unwrap(), Duration::minutes(450));
+        assert!(hours_to_duration(0.0).is_err());
+        assert!(hours_to_duration(-1.0).is_err());
+        assert!(hours_to_duration(f64::NAN).is_err());
+        assert!(hours_to_duration(f64::INFINITY).is_err());
+        assert!(hours_to_duration(1e300).is_err());
+    }
+
+    #[test]
+    fn test_do_timesheet_odd_times_fails() {
+        let hours_args = Hours {
+            times: vec!["03/03/2025 09:00".to_string()],
+            file: None,
+            break_minutes: 0,
+            daily_overtime: None,
+            weekly_overtime: None,
+        };
+        assert!(do_timesheet(&hours_args, false).is_err());
+    }
+
+    #[test]
+    fn test_do_timesheet() {
+        let hours_args = Hours {
+            times: vec![
+                "03/03/2025 09:00".to_string(),
+                "03/03/2025 17:30".to_string(),
+            ],
+            file: None,
+            break_minutes: 30,
+            daily_overtime: Some(7.5),
+            weekly_overtime: None,
+        };
+        assert!(do_timesheet(&hours_args, false).is_ok());
+    }
+}
@@ -0,0 +1,199 @@
+use crate::dateinfo::{self, DateTimeKeeper, holidays};
+use anyhow::{Result, ensure};
+use lifestuff_types::dateinfo::range::{DayKind, DayOfMonth, Range, RangeWeekday};
+use std::collections::HashSet;
+use time::{Date, Weekday};
+
+fn get_range_date(input: &str, verbose: bool) -> Result<DateTimeKeeper> {
+    if input.eq_ignore_ascii_case("today") {
+        dateinfo::get_date_from_string_arg(None, verbose)
+    } else {
+        dateinfo::get_date_from_string_arg(Some(input), verbose)
+    }
+}
+
+fn to_weekday(day: &RangeWeekday) -> Weekday {
+    match day {
+        RangeWeekday::Monday => Weekday::Monday,
+        RangeWeekday::Tuesday => Weekday::Tuesday,
+        RangeWeekday::Wednesday => Weekday::Wednesday,
+        RangeWeekday::Thursday => Weekday::Thursday,
+        RangeWeekday::Friday => Weekday::Friday,
+        RangeWeekday::Saturday => Weekday::Saturday,
+        RangeWeekday::Sunday => Weekday::Sunday,
+    }
+}
+
+fn is_weekend(date: &Date) -> bool {
+    matches!(date.weekday(), Weekday::Saturday | Weekday::Sunday)
+}
+
+fn matches_filters(date: &Date, range_args: &Range, bank_holidays: &HashSet<Date>) -> bool {
+    let kind_matches = match range_args.kind {
+        None => true,
+        Some(DayKind::Weekday) => !is_weekend(date),
+        Some(DayKind::Weekend) => is_weekend(date),
+        Some(DayKind::BusinessDay) => !is_weekend(date) && !bank_holidays.contains(date),
+    };
+
+    let day_matches = range_args.day.is_empty()
+        || range_args
+            .day
+            .iter()
+            .any(|day| to_weekday(day) == date.weekday());
+
+    let dom_matches = range_args.dom.is_empty()
+        || range_args.dom.iter().any(|dom| match dom {
+            DayOfMonth::Day(day) => date.day() == *day,
+            DayOfMonth::Last => date.day() == time::util::days_in_month(date.month(), date.year()),
+        });
+
+    kind_matches && day_matches && dom_matches
+}
+
+fn dates_in_range(start: &Date, end: &Date, range_args: &Range) -> Result<Vec<Date>> {
+    ensure!(
+        start <= end,
+        "Range start {:?} is after range end {:?}",
+        start,
+        end
+    );
+
+    let mut bank_holidays = HashSet::new();
+    if range_args.kind == Some(DayKind::BusinessDay) {
+        for year in start.year()..=end.year() {
+            bank_holidays.extend(holidays::bank_holidays(year)?);
+        }
+    }
+
+    let mut dates = Vec::new();
+    let mut current = *start;
+    loop {
+        if matches_filters(&current, range_args, &bank_holidays) {
+            dates.push(current);
+        }
+        if current == *end {
+            break;
+        }
+        current = current.next_day().unwrap();
+    }
+
+    Ok(dates)
+}
+
+pub fn do_date_range(range_args: &Range, verbose: bool) -> Result<()> {
+    if verbose {
+        println!("Args were: {:?}", range_args)
+    }
+
+    let start = get_range_date(&range_args.start, verbose)?;
+    let end = get_range_date(&range_args.end, verbose)?;
+    let dates = dates_in_range(&start.date(), &end.date(), range_args)?;
+
+    if range_args.count {
+        println!("{}", dates.len());
+        return Ok(());
+    }
+
+    for date in &dates {
+        println!(
+            "{:02}/{:02}/{} {}",
+            date.day(),
+            u8::from(date.month()),
+            date.year(),
+            date.weekday()
+        );
+    }
+    if verbose {
+        println!("{} matching dates", dates.len());
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::macros::date;
+
+    fn range_args(kind: Option<DayKind>, day: Vec<RangeWeekday>, dom: Vec<DayOfMonth>) -> Range {
+        Range {
+            start: "01/12/2025".to_string(),
+            end: "31/01/2026".to_string(),
+            kind,
+            day,
+            dom,
+            count: false,
+        }
+    }
+
+    fn count(args: &Range) -> usize {
+        dates_in_range(&date!(2025 - 12 - 01), &date!(2026 - 01 - 31), args)
+            .unwrap()
+            .len()
+    }
+
+    #[test]
+    fn test_dates_in_range_unfiltered() {
+        assert_eq!(count(&range_args(None, vec![], vec![])), 62);
+    }
+
+    #[test]
+    fn test_dates_in_range_day_of_week() {
+        let args = range_args(None, vec![RangeWeekday::Saturday], vec![]);
+        assert_eq!(count(&args), 9);
+    }
+
+    #[test]
+    fn test_dates_in_range_kinds() {
+        assert_eq!(
+            count(&range_args(Some(DayKind::Weekday), vec![], vec![])),
+            45
+        );
+        assert_eq!(
+            count(&range_args(Some(DayKind::Weekend), vec![], vec![])),
+            17
+        );
+        // Christmas Day, Boxing Day and New Year's Day are excluded
+        assert_eq!(
+            count(&range_args(Some(DayKind::BusinessDay), vec![], vec![])),
+            42
+        );
+    }
+
+    #[test]
+    fn test_dates_in_range_day_of_month() {
+        let args = range_args(None, vec![], vec![DayOfMonth::Last, DayOfMonth::Day(15)]);
+        let dates = dates_in_range(&date!(2025 - 12 - 01), &date!(2026 - 01 - 31), &args).unwrap();
+        assert_eq!(
+            dates,
+            vec![
+                date!(2025 - 12 - 15),
+                date!(2025 - 12 - 31),
+                date!(2026 - 01 - 15),
+                date!(2026 - 01 - 31),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_dates_in_range_start_after_end_fails() {
+        let args = range_args(None, vec![], vec![]);
+        assert!(dates_in_range(&date!(2026 - 01 - 31), &date!(2025 - 12 - 01), &args).is_err());
+    }
+
+    #[test]
+    fn test_day_of_month_parse() {
+        assert_eq!("last".parse::<DayOfMonth>(), Ok(DayOfMonth::Last));
+        assert_eq!("15".parse::<DayOfMonth>(), Ok(DayOfMonth::Day(15)));
+        assert!("32".parse::<DayOfMonth>().is_err());
+        assert!("0".parse::<DayOfMonth>().is_err());
+    }
+
+    #[test]
+    fn test_do_date_range_count() {
+        let mut args = range_args(Some(DayKind::BusinessDay), vec![], vec![]);
+        args.count = true;
+        assert!(do_date_range(&args, false).is_ok());
+    }
+}
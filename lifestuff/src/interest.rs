@@ -1,31 +1,98 @@
+mod compare;
+mod debts;
+mod from_mortgage;
+mod inflation;
+mod montecarlo;
+mod offers;
+mod offset;
+mod savings;
+mod scenario;
+mod schedule;
+mod solve;
 mod tests;
 
+use crate::csv_utils;
 use crate::dateinfo::{DateTimeKeeper, get_date_from_string_arg};
-use anyhow::{Ok, Result, ensure};
-use lifestuff_types::interest::Interest;
+use anyhow::{Context, Ok, Result, ensure};
+use lifestuff_types::interest::solve::SolveFor;
+use lifestuff_types::interest::{
+    Anniversary, Compounding, DayCount, Interest, InterestOperation, OutputFormat, RateChange,
+};
+use lifestuff_types::money::{Money, RoundingMode};
+use schedule::{AmortizationSchedule, ScheduleRow};
+use std::fs;
 use time::{Date, Month, util};
 
+/// How far ahead the simulator projects when no end date is given
+const MAX_TERM_YEARS: i32 = 100;
+
+/// Percentage of the balance most lenders allow to be overpaid each year without a charge
+const DEFAULT_ALLOWANCE_PCT: u8 = 10;
+
+/// Everything the simulator needs to project the loan
+#[derive(Debug, Clone)]
+struct LoanParameters {
+    start_date: Date,
+    end_date: Date,
+    /// No end date was given, so `end_date` is only a safety cap and the simulation runs until
+    /// the balance is cleared
+    open_ended: bool,
+    /// Stop an open-ended run once the balance isn't falling, even with rate changes still to come.
+    /// Used for generated rate paths, where a later fall is as likely as a further rise.
+    stop_when_growing: bool,
+    /// End of the mortgage term. The payment is recalculated on each rate change to clear the
+    /// balance by then, and kept the same when there is no term.
+    term_end: Option<Date>,
+    principal: Money,
+    monthly_payment: Money,
+    /// Annual interest rate (%) at the start date
+    interest_rate: f64,
+    /// New annual interest rates (%) keyed by the payment date they apply from, in date order
+    rate_changes: Vec<(Date, f64)>,
+    max_annual_repayment_pct: Option<u8>,
+    annual_downpayment: Option<Money>,
+    /// When the annual overpayment allowance resets and the annual overpayment is made
+    anniversary: Anniversary,
+    /// Early repayment charges (%) on overpayments above the allowance, for each allowance period
+    /// starting with the current one
+    early_repayment_charges: Vec<f64>,
+    /// Paid on top of the monthly payment every month
+    monthly_overpayment: Money,
+    /// Savings balances offset against the loan, keyed by the date they apply from, in date order
+    offset: Vec<(Date, Money)>,
+    rounding: RoundingMode,
+    compounding: Compounding,
+    day_count: DayCount,
+}
+
 fn get_start_of_next_month(verbose: bool) -> Result<DateTimeKeeper> {
     let target_date = get_date_from_string_arg(None, verbose)?;
-    let next_month = target_date.date().month().next();
 
-    target_date
-        .date()
-        .replace_day(1)?
-        .replace_month(next_month)?;
-    Ok(target_date)
+    target_date.next_month()
 }
 
 fn get_end_of_mortgage_period(end_date: &str, verbose: bool) -> Result<DateTimeKeeper> {
-    let target_date = get_date_from_string_arg(Some(end_date), verbose)?;
+    let mut target_date = get_date_from_string_arg(Some(end_date), verbose)?;
     let num_days_in_month =
         util::days_in_month(target_date.date().month(), target_date.date().year());
 
-    target_date.date().replace_day(num_days_in_month)?;
+    target_date.set_day(num_days_in_month)?;
 
     Ok(target_date)
 }
 
+/// Rejects an end date that falls before the simulation starts, which would leave nothing to
+/// simulate
+fn ensure_ends_after_start(start_date: &Date, end_date: &Date) -> Result<()> {
+    ensure!(
+        end_date > start_date,
+        "The end date {} is before the simulation starts on {}",
+        end_date,
+        start_date
+    );
+    Ok(())
+}
+
 fn is_first_of_month(date: &Date) -> bool {
     date.day() == 1
 }
@@ -33,105 +100,552 @@ fn is_first_of_month(date: &Date) -> bool {
 fn is_beginning_of_year(date: &Date) -> bool {
     date.month() == Month::January
 }
+
+/// Date the anniversary falls on in `year`. A 29 February anniversary is observed on the 28th
+/// in other years.
+fn anniversary_in_year(anniversary: Anniversary, year: i32) -> Date {
+    let month = Month::try_from(anniversary.month).unwrap_or(Month::January);
+    let day = anniversary.day.min(util::days_in_month(month, year));
+
+    Date::from_calendar_date(year, month, day).unwrap()
+}
+
+fn is_anniversary(date: &Date, anniversary: Anniversary) -> bool {
+    *date == anniversary_in_year(anniversary, date.year())
+}
+
+/// Most recent anniversary on or before `date`
+fn previous_anniversary(date: &Date, anniversary: Anniversary) -> Date {
+    let this_year = anniversary_in_year(anniversary, date.year());
+    if this_year <= *date {
+        this_year
+    } else {
+        anniversary_in_year(anniversary, date.year() - 1)
+    }
+}
+
+/// First anniversary after `date`
+fn next_anniversary(date: &Date, anniversary: Anniversary) -> Date {
+    let this_year = anniversary_in_year(anniversary, date.year());
+    if this_year > *date {
+        this_year
+    } else {
+        anniversary_in_year(anniversary, date.year() + 1)
+    }
+}
+
+/// Fraction of a year's interest that accrues on `date` under the day-count convention
+fn daily_year_fraction(date: &Date, day_count: DayCount) -> f64 {
+    match day_count {
+        DayCount::ActualActual => 1.0 / f64::from(util::days_in_year(date.year())),
+        DayCount::Actual365 => 1.0 / 365.0,
+        DayCount::Actual360 => 1.0 / 360.0,
+        DayCount::Thirty360 => {
+            30.0 / f64::from(util::days_in_month(date.month(), date.year())) / 360.0
+        }
+    }
+}
+
+/// Number of monthly payments from `from` up to and including the month of `to`
+fn payments_remaining(from: &Date, to: &Date) -> i32 {
+    (to.year() - from.year()) * 12
+        + (u8::from(to.month()) as i32 - u8::from(from.month()) as i32)
+        + 1
+}
+
+/// Level monthly payment that clears `balance` over `payments` months at the annual `rate` (%)
+fn annuity_payment(balance: Money, rate: f64, payments: i32, rounding: RoundingMode) -> Money {
+    if payments <= 0 {
+        return balance;
+    }
+
+    let monthly_rate = rate / 100.0 / 12.0;
+    let payment = if monthly_rate == 0.0 {
+        balance.to_f64() / payments as f64
+    } else {
+        balance.to_f64() * monthly_rate / (1.0 - (1.0 + monthly_rate).powi(-payments))
+    };
+
+    Money::from_f64(payment).round_to_penny(rounding)
+}
+
+fn parse_rate_schedule(contents: &str) -> Result<Vec<RateChange>> {
+    csv_utils::parse_rows(contents, |line| {
+        line.parse::<RateChange>()
+            .map_err(anyhow::Error::msg)
+            .context("invalid rate change")
+    })
+}
+
+/// Resolves the rate changes passed on the command line and in a schedule file to the payment
+/// dates they take effect on. Changes part way through a month apply from the next payment.
+fn get_rate_changes(
+    interest_args: &Interest,
+    start_date: &Date,
+    verbose: bool,
+) -> Result<Vec<(Date, f64)>> {
+    let mut changes = interest_args.rate_changes.clone();
+    if let Some(path) = &interest_args.rate_schedule {
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("Unable to read rate schedule {}", path.display()))?;
+        changes.extend(parse_rate_schedule(&contents)?);
+    }
+
+    let mut timeline = changes
+        .iter()
+        .map(|change| {
+            let mut effective = get_date_from_string_arg(Some(&change.date), verbose)?;
+            if !is_first_of_month(&effective.date()) {
+                effective = effective.next_month()?;
+            }
+            ensure!(
+                effective.date() > *start_date,
+                "Rate change on {} takes effect on {}, which is not after the simulation start of {}. Pass the current rate with --interest-rate instead",
+                change.date,
+                effective.date(),
+                start_date
+            );
+            Ok((effective.date(), change.rate))
+        })
+        .collect::<Result<Vec<(Date, f64)>>>()?;
+
+    timeline.sort_by_key(|(date, _)| *date);
+    if let Some(pair) = timeline.windows(2).find(|pair| pair[0].0 == pair[1].0) {
+        anyhow::bail!("More than one rate change takes effect on {}", pair[0].0);
+    }
+
+    Ok(timeline)
+}
 pub fn handle_interest_calculations(interest_args: Interest, verbose: bool) -> Result<()> {
     if verbose {
         println!("Interest Args: {:?}", interest_args);
     }
+    let interest_args = scenario::resolve(interest_args, verbose)?;
 
-    ensure!(
-        interest_args.principal > 0f32,
-        "Can only calculate interest on a positive principal. {0} was passed in",
-        interest_args.principal
+    // Debts bring their own balances and rates, so none of the loan options are needed
+    if let Some(InterestOperation::Debts(debts_args)) = &interest_args.operation {
+        let start_date = get_start_of_next_month(verbose)?;
+        let end_date = match &interest_args.end_date {
+            Some(end_date) => get_end_of_mortgage_period(end_date, verbose)?,
+            None => start_date.apply_year_delta(MAX_TERM_YEARS)?,
+        };
+        ensure_ends_after_start(&start_date.date(), &end_date.date())?;
+        let params = debts::DebtParameters {
+            start_date: start_date.date(),
+            end_date: end_date.date(),
+            budget: debts_args.budget,
+            rounding: interest_args.rounding,
+            compounding: interest_args.convention,
+            day_count: interest_args.day_count,
+        };
+        let plans = debts::load_and_plan(&params, debts_args)?;
+        print!(
+            "{}",
+            debts::render_plans(&plans, &params.end_date, interest_args.format)?
+        );
+        return Ok(());
+    }
+
+    let mortgage = if interest_args.from_mortgage {
+        Some(crate::mortgage::fetch_mortgage_summary(
+            interest_args.endpoint.clone(),
+            verbose,
+        )?)
+    } else {
+        None
+    };
+    if verbose && let Some(mortgage) = &mortgage {
+        println!("Mortgage summary: {:?}", mortgage);
+    }
+
+    let principal = interest_args
+        .principal
+        .or(mortgage.as_ref().map(|mortgage| mortgage.current_balance))
+        .context("--principal is required unless using --from-mortgage")?;
+    let interest_rate = interest_args
+        .interest_rate
+        .or(mortgage
+            .as_ref()
+            .map(|mortgage| mortgage.interest_rate * 100.0))
+        .context("--interest-rate is required unless using --from-mortgage")?;
+
+    let is_savings = matches!(
+        &interest_args.operation,
+        Some(InterestOperation::Savings(_))
     );
-    let mortgage_end_date = get_end_of_mortgage_period(&interest_args.end_date, verbose)?;
-
-    let mortgage_start_date = get_start_of_next_month(verbose)?;
-
-    calculate_interest_data_for_period(
-        &mortgage_start_date,
-        &mortgage_end_date,
-        interest_args.repayment,
-        interest_args.interest_rate / 100f32,
-        interest_args.principal,
-        interest_args.max_repayment_pct,
-        interest_args.annual_downpayment,
-        verbose,
+    if is_savings {
+        ensure!(
+            !principal.is_negative(),
+            "The opening savings balance can't be negative. {0} was passed in",
+            principal
+        );
+    } else {
+        ensure!(
+            principal.is_positive(),
+            "Can only calculate interest on a positive principal. {0} was passed in",
+            principal
+        );
+    }
+    let solving_for_payment = matches!(
+        &interest_args.operation,
+        Some(InterestOperation::Solve(solve_args)) if solve_args.solve_for == SolveFor::Payment
     );
+    // Offers work out their own payment from the end date when one is given
+    let is_offers = matches!(&interest_args.operation, Some(InterestOperation::Offers(_)));
+    let monthly_payment = match interest_args
+        .repayment
+        .or(mortgage.as_ref().map(|mortgage| mortgage.monthly_payment))
+    {
+        Some(repayment) => repayment,
+        None if solving_for_payment || is_savings || is_offers => Money::ZERO,
+        None => anyhow::bail!("--repayment is required unless solving for the monthly payment"),
+    };
+    let mortgage_start_date = match &mortgage {
+        Some(mortgage) => from_mortgage::resume_date(mortgage, verbose)?,
+        None => get_start_of_next_month(verbose)?,
+    };
+
+    let mortgage_end_date = match &interest_args.end_date {
+        Some(end_date) => get_end_of_mortgage_period(end_date, verbose)?,
+        None => mortgage_start_date.apply_year_delta(MAX_TERM_YEARS)?,
+    };
+    ensure_ends_after_start(&mortgage_start_date.date(), &mortgage_end_date.date())?;
+
+    let term_end = match &interest_args.term {
+        Some(term) => {
+            let term_end = get_end_of_mortgage_period(term, verbose)?.date();
+            ensure!(
+                term_end >= mortgage_start_date.date(),
+                "The mortgage term ends on {}, before the simulation starts on {}",
+                term_end,
+                mortgage_start_date.date()
+            );
+            Some(term_end)
+        }
+        None => match &mortgage {
+            Some(mortgage) => from_mortgage::term_end(mortgage, verbose)?
+                .filter(|term_end| *term_end >= mortgage_start_date.date()),
+            None => None,
+        },
+    };
+
+    ensure!(
+        interest_args
+            .early_repayment_charges
+            .iter()
+            .all(|pct| pct.is_finite() && *pct >= 0.0),
+        "Early repayment charges must be non-negative percentages, got: {:?}",
+        interest_args.early_repayment_charges
+    );
+
+    let rate_changes = get_rate_changes(&interest_args, &mortgage_start_date.date(), verbose)?;
+    if verbose {
+        println!("Rate changes: {:?}", rate_changes);
+    }
+
+    let params = LoanParameters {
+        start_date: mortgage_start_date.date(),
+        end_date: mortgage_end_date.date(),
+        open_ended: interest_args.end_date.is_none(),
+        stop_when_growing: false,
+        term_end,
+        principal,
+        monthly_payment,
+        interest_rate,
+        rate_changes,
+        max_annual_repayment_pct: interest_args.max_repayment_pct,
+        annual_downpayment: interest_args.annual_downpayment,
+        anniversary: interest_args.anniversary.unwrap_or_default(),
+        early_repayment_charges: interest_args.early_repayment_charges.clone(),
+        monthly_overpayment: Money::ZERO,
+        offset: offset::get_offset_balances(&interest_args, &mortgage_start_date.date(), verbose)?,
+        rounding: interest_args.rounding,
+        compounding: interest_args.convention,
+        day_count: interest_args.day_count,
+    };
+
+    let deflator = inflation::get_deflator(&interest_args, &mortgage_start_date.date(), verbose)?;
+    let today = get_date_from_string_arg(None, verbose)?.date();
+
+    let output = match &interest_args.operation {
+        Some(InterestOperation::Compare(compare_args)) => {
+            let scenarios = compare::compare_strategies(&params, compare_args)?;
+            compare::render_comparison(&scenarios, interest_args.format)?
+        }
+        Some(InterestOperation::Debts(_)) => unreachable!("Debts are planned without a loan"),
+        Some(InterestOperation::MonteCarlo(montecarlo_args)) => {
+            let summary = montecarlo::simulate_paths(&params, montecarlo_args, verbose)?;
+            montecarlo::render_summary(&summary, interest_args.format)?
+        }
+        Some(InterestOperation::Offers(offers_args)) => {
+            let summaries = offers::load_and_compare(&params, &offers_args.file)?;
+            offers::render_offers(&summaries, interest_args.format)?
+        }
+        Some(InterestOperation::Savings(savings_args)) => {
+            let mut projection = savings::project_savings(&params, savings_args)?;
+            if let Some(deflator) = &deflator {
+                inflation::deflate_savings(&mut projection, deflator, params.rounding);
+            }
+            savings::render_savings(&projection, interest_args.format)?
+        }
+        Some(InterestOperation::Solve(solve_args)) => {
+            let mut solution = solve::solve(&params, solve_args.solve_for)?;
+            solution.schedule.today = Some(today);
+            if let Some(deflator) = &deflator {
+                inflation::deflate_schedule(&mut solution.schedule, deflator, params.rounding);
+            }
+            solve::render_solution(&solution, solve_args.solve_for, interest_args.format)?
+        }
+        None => {
+            let mut schedule = calculate_interest_data_for_period(&params);
+            schedule.today = Some(today);
+            if let Some(deflator) = &deflator {
+                inflation::deflate_schedule(&mut schedule, deflator, params.rounding);
+            }
+            for warning in schedule_warnings(&schedule, &params) {
+                eprintln!("Warning: {warning}");
+            }
+            let mut output = schedule::render_schedule(&schedule, interest_args.format)?;
+            if !params.offset.is_empty() && interest_args.format == OutputFormat::Table {
+                let summary =
+                    offset::summarise_offset(&params, &schedule, interest_args.savings_rate);
+                output.push_str(&offset::render_offset_summary(&summary));
+            }
+            if let Some(mortgage) = &mortgage
+                && interest_args.format == OutputFormat::Table
+                && let Some(check) = from_mortgage::check_balance(mortgage, &params, verbose)?
+            {
+                output.push_str(&from_mortgage::render_balance_check(&check));
+            }
+            output
+        }
+    };
+    print!("{}", output);
 
     Ok(())
 }
 
-#[allow(clippy::too_many_arguments)]
-fn calculate_interest_data_for_period(
-    start_date: &DateTimeKeeper,
-    end_date: &DateTimeKeeper,
-    monthly_payment: f32,
-    interest_rate: f32,
-    mut principal: f32,
-    max_annual_repayment_pct: Option<u8>,
-    annual_downpayment: Option<f32>,
-    verbose: bool,
-) {
-    let mut current_date = start_date.date();
-    let tota_num_days = (*end_date - *start_date).abs().whole_days();
-    let mut accrued_monthly_interest = 0.0;
-    let mut is_leap_year = time::util::is_leap_year(current_date.year());
-    let mut total_paid = 0.0;
-    let original_principal = principal;
-
-    let using_interest = annual_downpayment.is_none();
-
-    for _ in 1..=tota_num_days {
-        match is_first_of_month(&current_date) {
-            true => {
-                if verbose {
-                    println!(
-                        "Beginning of the month: {:?}. Accrued interest last month was {:.2}",
-                        current_date.to_calendar_date(),
-                        accrued_monthly_interest
-                    );
+/// Flags a payment that doesn't cover the interest, and a balance never cleared when projecting
+/// to the payoff date
+fn schedule_warnings(schedule: &AmortizationSchedule, params: &LoanParameters) -> Vec<String> {
+    let mut warnings = Vec::new();
+    if let Some(row) = schedule.rows.iter().find(|row| {
+        row.overpayment.is_zero() && !row.closing_balance.is_zero() && row.interest >= row.payment
+    }) {
+        warnings.push(format!(
+            "the monthly payment of {:.2} doesn't cover the interest of {:.2} from {}, so the balance is not reducing",
+            row.payment, row.interest, row.date
+        ));
+    }
+    if schedule.diverging {
+        warnings.push(format!(
+            "the balance is not falling, so it is never cleared. The projection stops on {} with {:.2} owed",
+            schedule.rows.last().map(|row| row.date).unwrap_or(params.start_date),
+            schedule.closing_balance()
+        ));
+    } else if params.open_ended && schedule.payoff_date().is_none() {
+        warnings.push(format!(
+            "the balance is not cleared within {MAX_TERM_YEARS} years. {:.2} is still owed on {}",
+            schedule.closing_balance(),
+            params.end_date
+        ));
+    }
+    // With an ERC schedule the charges are reported with the schedule instead
+    if let Some(period) = schedule
+        .allowance_periods
+        .iter()
+        .find(|period| period.headroom.is_negative())
+        && params.early_repayment_charges.is_empty()
+    {
+        warnings.push(format!(
+            "overpayments of {:.2} in the year from {} exceed the allowance of {:.2}, so early repayment charges may apply",
+            period.overpaid, period.start, period.allowance
+        ));
+    }
+
+    warnings
+}
+
+/// Simulates the mortgage day by day between the two dates (inclusive), taking payments on the
+/// first of each month and then accruing interest for every day under the day-count convention.
+/// Interest is added to the balance daily, or at the end of each month for monthly and annual
+/// rest. It accrues at full precision and the balance is rounded to the penny when each month is
+/// closed, so every row of the schedule reconciles exactly. Stops early once the balance is
+/// cleared, or when open ended once the balance isn't falling with no rate or offset change left to
+/// turn it around (or at all with `stop_when_growing`), as it would otherwise grow for the whole
+/// safety cap.
+///
+/// The annual overpayment is made on the first payment date on or after each completion
+/// anniversary. Overpayments are tracked against an allowance for each year between
+/// anniversaries, taken as a percentage of the balance on the anniversary. Anything overpaid
+/// beyond it still reduces the balance, but incurs that period's early repayment charge.
+///
+/// When the rate changes, a new rate phase is started. With a mortgage term the monthly payment is
+/// recalculated so the balance would be cleared by the end of the term at the new rate, otherwise
+/// the payment stays the same. The end date only limits how far the simulation runs, so it is
+/// never used as the term.
+fn calculate_interest_data_for_period(params: &LoanParameters) -> AmortizationSchedule {
+    let rounding = params.rounding;
+    let mut current_date = params.start_date;
+    let mut principal = params.principal;
+    let mut monthly_payment = params.monthly_payment;
+    let mut interest_rate = params.interest_rate;
+    let mut rate_changes = params.rate_changes.iter().peekable();
+    let mut schedule = AmortizationSchedule::default();
+    let mut current_row: Option<ScheduleRow> = None;
+    // Interest accrued but not yet added to the balance, and the balance it accrues on, for
+    // conventions that don't compound daily
+    let mut accrued = Money::ZERO;
+    let mut rest_balance = principal;
+    let mut offsets = params.offset.iter().peekable();
+    let mut offset = Money::ZERO;
+    let allowance_pct = params
+        .max_annual_repayment_pct
+        .unwrap_or(DEFAULT_ALLOWANCE_PCT);
+    let mut annual_overpayment_due = false;
+    // An annual overpayment can turn a balance growing month to month around, so with one the
+    // change is measured over a year
+    let growth_months =
+        if params.annual_downpayment.is_some() || params.max_annual_repayment_pct.is_some() {
+            12
+        } else {
+            1
+        };
+
+    schedule.overpaying = params.annual_downpayment.is_some()
+        || params.max_annual_repayment_pct.is_some()
+        || params.monthly_overpayment.is_positive();
+    schedule.start_phase(current_date, interest_rate, monthly_payment);
+    // The balance on the last anniversary isn't known, so the current one stands in for it
+    let charge_pct = |period: usize| {
+        params
+            .early_repayment_charges
+            .get(period)
+            .copied()
+            .unwrap_or_default()
+    };
+    schedule.start_allowance_period(
+        previous_anniversary(&current_date, params.anniversary),
+        next_anniversary(&current_date, params.anniversary),
+        principal.percentage(allowance_pct.into(), rounding),
+        charge_pct(0),
+    );
+
+    let close_row = |mut row: ScheduleRow, principal: &mut Money, accrued: &mut Money| {
+        *principal = (*principal + std::mem::take(accrued)).round_to_penny(rounding);
+        row.closing_balance = *principal;
+        row.interest = row.closing_balance - row.opening_balance + row.payment + row.overpayment;
+        row
+    };
+
+    while current_date <= params.end_date && principal.is_positive() {
+        if is_first_of_month(&current_date) || current_row.is_none() {
+            if let Some(row) = current_row.take() {
+                schedule.push_row(close_row(row, &mut principal, &mut accrued));
+                if params.open_ended
+                    && (params.stop_when_growing
+                        || (rate_changes.peek().is_none() && offsets.peek().is_none()))
+                    && schedule.not_falling_over(growth_months)
+                {
+                    schedule.diverging = true;
+                    break;
                 }
-                accrued_monthly_interest = 0.0;
-                if is_beginning_of_year(&current_date) {
-                    is_leap_year = time::util::is_leap_year(current_date.year());
-                    if verbose {
-                        println!(
-                            "Beginning of the year: {:?} ",
-                            current_date.to_calendar_date()
-                        );
-                    }
+            }
+            current_row = Some(ScheduleRow {
+                date: current_date,
+                opening_balance: principal,
+                interest: Money::ZERO,
+                payment: Money::ZERO,
+                overpayment: Money::ZERO,
+                closing_balance: principal,
+                real: None,
+            });
+        }
+        let row = current_row.as_mut().unwrap();
+
+        if is_anniversary(&current_date, params.anniversary) {
+            if schedule
+                .allowance_periods
+                .last()
+                .is_none_or(|period| period.start != current_date)
+            {
+                schedule.start_allowance_period(
+                    current_date,
+                    next_anniversary(&current_date, params.anniversary),
+                    principal.percentage(allowance_pct.into(), rounding),
+                    charge_pct(schedule.allowance_periods.len()),
+                );
+            }
+            annual_overpayment_due = true;
+        }
 
-                    let repayment = if using_interest {
-                        let interest_repayment = max_annual_repayment_pct.unwrap() as f32 / 100.0;
-
-                        principal * interest_repayment
-                    } else {
-                        annual_downpayment.unwrap()
-                    };
-                    total_paid += repayment;
-                    principal -= repayment;
-                    if verbose {
-                        println!("The max repayment number is {repayment}");
-                        println!("After yearly payment the principal is now {principal}");
+        if is_first_of_month(&current_date) {
+            if annual_overpayment_due {
+                let repayment =
+                    match (params.annual_downpayment, params.max_annual_repayment_pct) {
+                        (Some(downpayment), _) => downpayment,
+                        (None, Some(_)) => schedule
+                            .allowance_periods
+                            .last()
+                            .map(|period| period.allowance)
+                            .unwrap_or_default(),
+                        (None, None) => Money::ZERO,
                     }
-                }
-                total_paid += monthly_payment;
-                principal -= monthly_payment;
+                    .min(principal);
+                row.overpayment = repayment;
+                principal -= repayment;
+                annual_overpayment_due = false;
             }
-            false => {
-                let daily_interest =
-                    interest_rate * principal / (if is_leap_year { 366.0 } else { 365.0 });
-                accrued_monthly_interest += daily_interest;
-                principal += daily_interest;
+            if let Some((_, new_rate)) =
+                rate_changes.next_if(|(effective, _)| *effective <= current_date)
+            {
+                interest_rate = *new_rate;
+                if let Some(term_end) = params.term_end {
+                    monthly_payment = annuity_payment(
+                        principal,
+                        interest_rate,
+                        payments_remaining(&current_date, &term_end),
+                        rounding,
+                    );
+                }
+                schedule.start_phase(current_date, interest_rate, monthly_payment);
             }
+            let payment = monthly_payment.min(principal);
+            row.payment = payment;
+            principal -= payment;
+
+            let overpayment = params.monthly_overpayment.min(principal);
+            row.overpayment += overpayment;
+            principal -= overpayment;
+            schedule.record_overpayment(row.overpayment, rounding);
         }
 
+        if current_date == params.start_date
+            || (is_first_of_month(&current_date) && is_beginning_of_year(&current_date))
+        {
+            rest_balance = principal;
+        }
+
+        while let Some((_, balance)) = offsets.next_if(|(from, _)| *from <= current_date) {
+            offset = *balance;
+        }
+
+        let year_fraction = daily_year_fraction(&current_date, params.day_count);
+        let daily_rate = interest_rate / 100.0 * year_fraction;
+        let charged_balance = |balance: Money| (balance - offset).max(Money::ZERO);
+        match params.compounding {
+            Compounding::Daily => principal += charged_balance(principal).mul_f64(daily_rate),
+            Compounding::Monthly => accrued += charged_balance(principal).mul_f64(daily_rate),
+            Compounding::Annual => accrued += charged_balance(rest_balance).mul_f64(daily_rate),
+        }
+        schedule.balance_years += principal.to_f64() * year_fraction;
+
         current_date = current_date.next_day().unwrap();
     }
 
-    println!(
-        "Current date is {:?}, Principal is now : {principal}, Total paid is {total_paid}. {:.2} paid in interest",
-        current_date,
-        total_paid - (original_principal - principal)
-    );
+    if let Some(row) = current_row {
+        schedule.push_row(close_row(row, &mut principal, &mut accrued));
+    }
+
+    schedule
 }
@@ -0,0 +1,193 @@
+use super::schedule::serialize_optional_date;
+use super::{LoanParameters, calculate_interest_data_for_period};
+use anyhow::{Context, Result, ensure};
+use lifestuff_types::interest::OutputFormat;
+use lifestuff_types::interest::compare::Compare;
+use lifestuff_types::money::Money;
+use serde::Serialize;
+use time::Date;
+
+/// Outcome of simulating the loan under one overpayment strategy
+#[derive(Debug, Clone, Serialize)]
+pub struct ScenarioSummary {
+    pub name: String,
+    /// Date of the final payment, or `None` if the balance is not cleared by the end date
+    #[serde(serialize_with = "serialize_optional_date")]
+    pub payoff_date: Option<Date>,
+    pub payments: usize,
+    pub total_interest: Money,
+    /// Interest saved compared with making no overpayments
+    pub interest_saved: Money,
+    /// Charged on overpayments above the annual allowance
+    pub early_repayment_charges: Money,
+    /// Interest saved less the early repayment charges
+    pub net_saving: Money,
+    /// Balance left at the end date
+    pub closing_balance: Money,
+}
+
+fn strategies(params: &LoanParameters, compare_args: &Compare) -> Vec<(String, LoanParameters)> {
+    // The allowance percentage is kept for the charges on overpayments above it. A zero
+    // downpayment stops it being overpaid in full each year.
+    let baseline = LoanParameters {
+        annual_downpayment: Some(Money::ZERO),
+        monthly_overpayment: Money::ZERO,
+        ..params.clone()
+    };
+
+    let mut strategies = vec![("No overpayment".to_string(), baseline.clone())];
+    if let Some(pct) = compare_args
+        .max_repayment_pct
+        .or(params.max_annual_repayment_pct)
+    {
+        strategies.push((
+            format!("{pct}% annual cap"),
+            LoanParameters {
+                max_annual_repayment_pct: Some(pct),
+                annual_downpayment: None,
+                ..baseline.clone()
+            },
+        ));
+    }
+    if let Some(downpayment) = compare_args
+        .annual_downpayment
+        .or(params.annual_downpayment)
+    {
+        strategies.push((
+            format!("{downpayment} a year"),
+            LoanParameters {
+                annual_downpayment: Some(downpayment),
+                ..baseline.clone()
+            },
+        ));
+    }
+    for overpayment in &compare_args.monthly_overpayment {
+        strategies.push((
+            format!("{overpayment} a month"),
+            LoanParameters {
+                monthly_overpayment: *overpayment,
+                ..baseline.clone()
+            },
+        ));
+    }
+
+    strategies
+}
+
+/// Runs the simulator once per overpayment strategy, starting with no overpayment as the baseline
+pub fn compare_strategies(
+    params: &LoanParameters,
+    compare_args: &Compare,
+) -> Result<Vec<ScenarioSummary>> {
+    let strategies = strategies(params, compare_args);
+    ensure!(
+        strategies.len() > 1,
+        "Nothing to compare. Pass --max-repayment-pct, --annual-downpayment or --monthly-overpayment"
+    );
+
+    let mut summaries: Vec<ScenarioSummary> = Vec::new();
+    for (name, strategy) in strategies {
+        let schedule = calculate_interest_data_for_period(&strategy);
+        let total_interest = schedule.total_interest();
+        let baseline_interest = summaries
+            .first()
+            .map(|baseline| baseline.total_interest)
+            .unwrap_or(total_interest);
+        let interest_saved = baseline_interest - total_interest;
+        let early_repayment_charges = schedule.total_charges();
+
+        summaries.push(ScenarioSummary {
+            name,
+            payoff_date: schedule.payoff_date(),
+            payments: schedule.rows.len(),
+            total_interest,
+            interest_saved,
+            early_repayment_charges,
+            net_saving: interest_saved - early_repayment_charges,
+            closing_balance: schedule.closing_balance(),
+        });
+    }
+
+    Ok(summaries)
+}
+
+fn format_payoff_date(date: Option<Date>) -> String {
+    date.map(|date| date.to_string())
+        .unwrap_or_else(|| "-".to_string())
+}
+
+fn render_table(summaries: &[ScenarioSummary]) -> String {
+    let mut output = format!(
+        "{:<20}{:>12}{:>10}{:>16}{:>16}{:>12}{:>16}{:>14}\n",
+        "Scenario", "Payoff", "Payments", "Interest", "Saved", "ERC", "Net saving", "Balance"
+    );
+    for summary in summaries {
+        output.push_str(&format!(
+            "{:<20}{:>12}{:>10}{:>16.2}{:>16.2}{:>12.2}{:>16.2}{:>14.2}\n",
+            summary.name,
+            format_payoff_date(summary.payoff_date),
+            summary.payments,
+            summary.total_interest,
+            summary.interest_saved,
+            summary.early_repayment_charges,
+            summary.net_saving,
+            summary.closing_balance
+        ));
+    }
+    // Only worth calling out when a charge could make overpaying more a false economy
+    let charged = summaries
+        .iter()
+        .any(|summary| summary.early_repayment_charges.is_positive());
+    if let Some(best) = summaries
+        .iter()
+        .skip(1)
+        .max_by_key(|summary| summary.net_saving)
+        .filter(|_| charged)
+    {
+        output.push_str(&format!(
+            "After early repayment charges the best net saving is {:.2}, from {}\n",
+            best.net_saving, best.name
+        ));
+    }
+    if summaries
+        .iter()
+        .any(|summary| summary.payoff_date.is_none())
+    {
+        output.push_str("Scenarios without a payoff date still have a balance at the end date\n");
+    }
+
+    output
+}
+
+fn render_csv(summaries: &[ScenarioSummary]) -> String {
+    let mut output =
+        "scenario,payoff_date,payments,total_interest,interest_saved,early_repayment_charges,net_saving,closing_balance\n"
+            .to_string();
+    for summary in summaries {
+        output.push_str(&format!(
+            "{},{},{},{:.2},{:.2},{:.2},{:.2},{:.2}\n",
+            summary.name,
+            summary
+                .payoff_date
+                .map(|date| date.to_string())
+                .unwrap_or_default(),
+            summary.payments,
+            summary.total_interest,
+            summary.interest_saved,
+            summary.early_repayment_charges,
+            summary.net_saving,
+            summary.closing_balance
+        ));
+    }
+
+    output
+}
+
+pub fn render_comparison(summaries: &[ScenarioSummary], format: OutputFormat) -> Result<String> {
+    match format {
+        OutputFormat::Table => Ok(render_table(summaries)),
+        OutputFormat::Csv => Ok(render_csv(summaries)),
+        OutputFormat::Json => serde_json::to_string_pretty(summaries)
+            .context("Unable to serialise strategy comparison to JSON"),
+    }
+}
@@ -0,0 +1,505 @@
+use super::schedule::serialize_optional_date;
+use super::{daily_year_fraction, is_beginning_of_year, is_first_of_month};
+use anyhow::{Context, Result, bail, ensure};
+use lifestuff_types::interest::debts::Debts;
+use lifestuff_types::interest::{Compounding, DayCount, OutputFormat};
+use lifestuff_types::money::{Money, RoundingMode};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use time::Date;
+
+/// A debt as read from the debts file
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Debt {
+    pub name: String,
+    pub balance: Money,
+    /// Annual interest rate (%)
+    pub rate: f64,
+    /// Minimum monthly payment
+    #[serde(alias = "minimum_payment")]
+    pub minimum: Money,
+}
+
+#[derive(Debug, Deserialize)]
+struct DebtsFile {
+    #[serde(rename = "debt")]
+    debts: Vec<Debt>,
+}
+
+/// Order the money left over after the minimum payments is put towards the debts in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Strategy {
+    /// Smallest balance first
+    Snowball,
+    /// Highest rate first
+    Avalanche,
+    /// The order given with --order, or the file order
+    Custom,
+}
+
+impl Strategy {
+    fn label(&self) -> &'static str {
+        match self {
+            Strategy::Snowball => "Snowball",
+            Strategy::Avalanche => "Avalanche",
+            Strategy::Custom => "Custom",
+        }
+    }
+}
+
+/// Everything the planner needs to simulate paying the debts off
+#[derive(Debug, Clone)]
+pub struct DebtParameters {
+    pub start_date: Date,
+    pub end_date: Date,
+    pub budget: Money,
+    pub rounding: RoundingMode,
+    pub compounding: Compounding,
+    pub day_count: DayCount,
+}
+
+/// How one debt is paid off under a plan
+#[derive(Debug, Clone, Serialize)]
+pub struct DebtOutcome {
+    pub name: String,
+    pub balance: Money,
+    pub rate: f64,
+    pub minimum: Money,
+    /// `None` when the debt isn't cleared by the end date
+    #[serde(serialize_with = "serialize_optional_date")]
+    pub payoff_date: Option<Date>,
+    pub payments: usize,
+    pub interest: Money,
+}
+
+/// The outcome of paying the debts off in one order
+#[derive(Debug, Clone, Serialize)]
+pub struct DebtPlan {
+    pub strategy: Strategy,
+    /// Debts in the order they are targeted
+    pub debts: Vec<DebtOutcome>,
+}
+
+impl DebtPlan {
+    pub fn total_interest(&self) -> Money {
+        self.debts.iter().map(|debt| debt.interest).sum()
+    }
+
+    /// When the last debt is cleared, or `None` if any is still owed at the end date
+    pub fn debt_free_date(&self) -> Option<Date> {
+        self.debts
+            .iter()
+            .map(|debt| debt.payoff_date)
+            .collect::<Option<Vec<Date>>>()?
+            .into_iter()
+            .max()
+    }
+}
+
+fn parse_debts_toml(contents: &str) -> Result<Vec<Debt>> {
+    Ok(toml::from_str::<DebtsFile>(contents)
+        .context("Unable to parse debts. Expected [[debt]] tables")?
+        .debts)
+}
+
+/// Parses debts from CSV with a `name,balance,rate,minimum` header row, in any order
+fn parse_debts_csv(contents: &str) -> Result<Vec<Debt>> {
+    let mut lines = contents
+        .lines()
+        .enumerate()
+        .map(|(line_number, line)| (line_number, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'));
+    let (_, header) = lines.next().context("No header row found in debts CSV")?;
+    let columns = header
+        .split(',')
+        .map(|column| match column.trim() {
+            "minimum_payment" => "minimum",
+            column => column,
+        })
+        .collect::<Vec<&str>>();
+    let position = |column: &str| {
+        columns
+            .iter()
+            .position(|name| *name == column)
+            .with_context(|| format!("Debts CSV header is missing the `{column}` column"))
+    };
+    let (name, balance, rate, minimum) = (
+        position("name")?,
+        position("balance")?,
+        position("rate")?,
+        position("minimum")?,
+    );
+
+    lines
+        .map(|(line_number, line)| {
+            let fields = line.split(',').map(str::trim).collect::<Vec<&str>>();
+            ensure!(
+                fields.len() == columns.len(),
+                "Line {}: expected {} fields but found {}",
+                line_number + 1,
+                columns.len(),
+                fields.len()
+            );
+            let money = |index: usize, column: &str| -> Result<Money> {
+                fields[index]
+                    .parse::<Money>()
+                    .map_err(anyhow::Error::msg)
+                    .context(format!("Line {}: invalid {column}", line_number + 1))
+            };
+
+            Ok(Debt {
+                name: fields[name].to_string(),
+                balance: money(balance, "balance")?,
+                rate: fields[rate]
+                    .trim_end_matches('%')
+                    .parse()
+                    .context(format!("Line {}: invalid rate", line_number + 1))?,
+                minimum: money(minimum, "minimum")?,
+            })
+        })
+        .collect()
+}
+
+/// Reads debts from a `.csv` file, or TOML otherwise
+pub(super) fn parse_debts(path: &Path, contents: &str) -> Result<Vec<Debt>> {
+    let debts = match path.extension().and_then(|extension| extension.to_str()) {
+        Some(extension) if extension.eq_ignore_ascii_case("csv") => parse_debts_csv(contents)?,
+        _ => parse_debts_toml(contents)?,
+    };
+    ensure!(!debts.is_empty(), "No debts found in {}", path.display());
+    for (index, debt) in debts.iter().enumerate() {
+        ensure!(
+            debt.balance.is_positive(),
+            "Debt '{}' must have a positive balance. {} was given",
+            debt.name,
+            debt.balance
+        );
+        ensure!(
+            debt.rate.is_finite() && debt.rate >= 0.0,
+            "Debt '{}' has an invalid rate of {}",
+            debt.name,
+            debt.rate
+        );
+        ensure!(
+            !debt.minimum.is_negative(),
+            "Debt '{}' has a negative minimum payment",
+            debt.name
+        );
+        ensure!(
+            !debts[..index]
+                .iter()
+                .any(|other| other.name.eq_ignore_ascii_case(&debt.name)),
+            "More than one debt is named '{}'",
+            debt.name
+        );
+    }
+
+    Ok(debts)
+}
+
+/// Indices of `debts` in the order the strategy targets them. Ties keep the file order.
+pub(super) fn target_order(
+    debts: &[Debt],
+    strategy: Strategy,
+    order: &[String],
+) -> Result<Vec<usize>> {
+    let mut indices = (0..debts.len()).collect::<Vec<usize>>();
+    match strategy {
+        Strategy::Snowball => indices.sort_by_key(|index| debts[*index].balance),
+        Strategy::Avalanche => indices.sort_by(|a, b| debts[*b].rate.total_cmp(&debts[*a].rate)),
+        Strategy::Custom => {
+            let mut named = order
+                .iter()
+                .map(|name| {
+                    debts
+                        .iter()
+                        .position(|debt| debt.name.eq_ignore_ascii_case(name.trim()))
+                        .with_context(|| format!("No debt named '{}' to order", name.trim()))
+                })
+                .collect::<Result<Vec<usize>>>()?;
+            if let Some(index) = named
+                .iter()
+                .enumerate()
+                .find_map(|(position, index)| named[..position].contains(index).then_some(index))
+            {
+                bail!(
+                    "'{}' is named more than once in --order",
+                    debts[*index].name
+                );
+            }
+            indices.retain(|index| !named.contains(index));
+            named.extend(indices);
+            indices = named;
+        }
+    }
+
+    Ok(indices)
+}
+
+/// Running totals for one debt during the simulation
+#[derive(Debug, Clone)]
+struct DebtState {
+    balance: Money,
+    /// Interest accrued but not yet added to the balance, for conventions that don't compound
+    /// daily
+    accrued: Money,
+    /// Balance interest accrues on under annual rest
+    rest_balance: Money,
+    /// Balance after the month's payment, which the month's interest is measured from
+    paid_balance: Money,
+    interest: Money,
+    payments: usize,
+    payoff_date: Option<Date>,
+}
+
+/// Simulates paying the debts off day by day with the same accrual rules as the loan schedule.
+/// On the first of each month every debt gets its minimum payment, then whatever is left of the
+/// budget goes to the first debt in `order` with a balance, rolling on to the next once it is
+/// cleared. Interest accrues at full precision and each balance is rounded to the penny when the
+/// month is closed.
+pub(super) fn simulate_plan(
+    params: &DebtParameters,
+    debts: &[Debt],
+    order: &[usize],
+) -> Vec<DebtOutcome> {
+    let rounding = params.rounding;
+    let mut states = debts
+        .iter()
+        .map(|debt| DebtState {
+            balance: debt.balance,
+            accrued: Money::ZERO,
+            rest_balance: debt.balance,
+            paid_balance: debt.balance,
+            interest: Money::ZERO,
+            payments: 0,
+            payoff_date: None,
+        })
+        .collect::<Vec<DebtState>>();
+
+    let close_month = |states: &mut [DebtState]| {
+        for state in states.iter_mut() {
+            let balance =
+                (state.balance + std::mem::take(&mut state.accrued)).round_to_penny(rounding);
+            state.interest += balance - state.paid_balance;
+            state.balance = balance;
+        }
+    };
+
+    let mut current_date = params.start_date;
+    while current_date <= params.end_date && states.iter().any(|state| state.balance.is_positive())
+    {
+        if is_first_of_month(&current_date) {
+            if current_date != params.start_date {
+                close_month(&mut states);
+            }
+
+            let mut available = params.budget;
+            let mut pay = |state: &mut DebtState, amount: Money| {
+                let amount = amount.min(state.balance).min(available);
+                if amount.is_positive() {
+                    state.balance -= amount;
+                    available -= amount;
+                    if state.balance.is_zero() {
+                        state.payoff_date = Some(current_date);
+                    }
+                }
+                amount
+            };
+            let mut paid = vec![false; states.len()];
+            for (index, (state, debt)) in states.iter_mut().zip(debts).enumerate() {
+                paid[index] = pay(state, debt.minimum).is_positive();
+            }
+            for index in order {
+                paid[*index] |= pay(&mut states[*index], params.budget).is_positive();
+            }
+            for (state, paid) in states.iter_mut().zip(paid) {
+                state.payments += usize::from(paid);
+                state.paid_balance = state.balance;
+            }
+
+            if current_date == params.start_date || is_beginning_of_year(&current_date) {
+                for state in &mut states {
+                    state.rest_balance = state.balance;
+                }
+            }
+        }
+
+        let year_fraction = daily_year_fraction(&current_date, params.day_count);
+        for (state, debt) in states.iter_mut().zip(debts) {
+            let daily_rate = debt.rate / 100.0 * year_fraction;
+            match params.compounding {
+                Compounding::Daily => state.balance += state.balance.mul_f64(daily_rate),
+                Compounding::Monthly => state.accrued += state.balance.mul_f64(daily_rate),
+                Compounding::Annual => state.accrued += state.rest_balance.mul_f64(daily_rate),
+            }
+        }
+
+        current_date = current_date.next_day().unwrap();
+    }
+    close_month(&mut states);
+
+    order
+        .iter()
+        .map(|index| {
+            let (debt, state) = (&debts[*index], &states[*index]);
+            DebtOutcome {
+                name: debt.name.clone(),
+                balance: debt.balance,
+                rate: debt.rate,
+                minimum: debt.minimum,
+                payoff_date: state.payoff_date,
+                payments: state.payments,
+                interest: state.interest,
+            }
+        })
+        .collect()
+}
+
+/// Simulates the snowball, avalanche and custom orders from the same budget
+pub fn plan_debts(
+    params: &DebtParameters,
+    debts: &[Debt],
+    order: &[String],
+) -> Result<Vec<DebtPlan>> {
+    let minimums = debts.iter().map(|debt| debt.minimum).sum::<Money>();
+    ensure!(
+        params.budget >= minimums,
+        "The monthly budget of {:.2} doesn't cover the minimum payments of {:.2}",
+        params.budget,
+        minimums
+    );
+
+    // A balance whose payments don't cover its interest never falls, so would only grow until
+    // the end of the simulation
+    let monthly_interest = |debt: &Debt| {
+        debt.balance
+            .mul_f64(debt.rate / 100.0 / 12.0)
+            .round_to_penny(params.rounding)
+    };
+    let interest = debts.iter().map(monthly_interest).sum::<Money>();
+    ensure!(
+        params.budget > interest,
+        "The monthly budget of {:.2} doesn't cover the monthly interest of about {:.2}, so the debts are never paid off",
+        params.budget,
+        interest
+    );
+    if let Some(debt) = debts
+        .iter()
+        .find(|debt| debt.balance.is_positive() && debt.minimum < monthly_interest(debt))
+    {
+        bail!(
+            "The minimum payment of {:.2} on '{}' doesn't cover its monthly interest of about {:.2}",
+            debt.minimum,
+            debt.name,
+            monthly_interest(debt)
+        );
+    }
+
+    [Strategy::Snowball, Strategy::Avalanche, Strategy::Custom]
+        .into_iter()
+        .map(|strategy| {
+            Ok(DebtPlan {
+                strategy,
+                debts: simulate_plan(params, debts, &target_order(debts, strategy, order)?),
+            })
+        })
+        .collect()
+}
+
+fn format_payoff_date(date: Option<Date>) -> String {
+    date.map(|date| date.to_string())
+        .unwrap_or_else(|| "not cleared".to_string())
+}
+
+fn render_table(plans: &[DebtPlan], end_date: &Date) -> String {
+    let mut output = String::new();
+    for plan in plans {
+        output.push_str(&format!(
+            "{}: {} with {:.2} total interest\n",
+            plan.strategy.label(),
+            match plan.debt_free_date() {
+                Some(date) => format!("debt free on {date}"),
+                None => format!("not debt free by {end_date}"),
+            },
+            plan.total_interest()
+        ));
+        output.push_str(&format!(
+            "{:<24}{:>12}{:>8}{:>10}{:>14}{:>10}{:>12}\n",
+            "Debt", "Balance", "Rate", "Minimum", "Payoff", "Payments", "Interest"
+        ));
+        for debt in &plan.debts {
+            output.push_str(&format!(
+                "{:<24}{:>12.2}{:>7.2}%{:>10.2}{:>14}{:>10}{:>12.2}\n",
+                debt.name,
+                debt.balance,
+                debt.rate,
+                debt.minimum,
+                format_payoff_date(debt.payoff_date),
+                debt.payments,
+                debt.interest
+            ));
+        }
+        output.push('\n');
+    }
+
+    if let Some(best) = plans.iter().min_by_key(|plan| plan.total_interest())
+        && let Some(worst) = plans.iter().max_by_key(|plan| plan.total_interest())
+    {
+        if best.total_interest() == worst.total_interest() {
+            output.push_str("Every order pays the same interest\n");
+        } else {
+            output.push_str(&format!(
+                "{} pays the least interest, {:.2} less than {}\n",
+                best.strategy.label(),
+                worst.total_interest() - best.total_interest(),
+                worst.strategy.label().to_lowercase()
+            ));
+        }
+    }
+
+    output
+}
+
+fn render_csv(plans: &[DebtPlan]) -> String {
+    let mut output =
+        "strategy,debt,balance,rate,minimum,payoff_date,payments,interest\n".to_string();
+    for plan in plans {
+        for debt in &plan.debts {
+            output.push_str(&format!(
+                "{},{},{:.2},{:.2},{:.2},{},{},{:.2}\n",
+                plan.strategy.label().to_lowercase(),
+                debt.name,
+                debt.balance,
+                debt.rate,
+                debt.minimum,
+                debt.payoff_date
+                    .map(|date| date.to_string())
+                    .unwrap_or_default(),
+                debt.payments,
+                debt.interest
+            ));
+        }
+    }
+
+    output
+}
+
+pub fn render_plans(plans: &[DebtPlan], end_date: &Date, format: OutputFormat) -> Result<String> {
+    match format {
+        OutputFormat::Table => Ok(render_table(plans, end_date)),
+        OutputFormat::Csv => Ok(render_csv(plans)),
+        OutputFormat::Json => {
+            serde_json::to_string_pretty(plans).context("Unable to serialise debt plans to JSON")
+        }
+    }
+}
+
+/// Loads the debts file and plans paying them off from the budget
+pub fn load_and_plan(params: &DebtParameters, args: &Debts) -> Result<Vec<DebtPlan>> {
+    let contents = fs::read_to_string(&args.file)
+        .with_context(|| format!("Unable to read debts file {}", args.file.display()))?;
+
+    plan_debts(params, &parse_debts(&args.file, &contents)?, &args.order)
+}
@@ -0,0 +1,118 @@
+use super::{LoanParameters, calculate_interest_data_for_period};
+use crate::dateinfo::{DateTimeKeeper, get_date_from_string_arg};
+use anyhow::{Context, Result};
+use lifestuff_types::money::Money;
+use lifestuff_types::mortgage::MortgageSummary;
+use time::Date;
+use time::macros::format_description;
+
+/// The balance the mortgage's original terms project for the last payment, against what is
+/// actually owed
+#[derive(Debug, Clone)]
+pub struct BalanceCheck {
+    pub date: Date,
+    pub projected: Money,
+    pub actual: Money,
+}
+
+impl BalanceCheck {
+    /// How far the actual balance is below the projected one, negative when behind schedule
+    pub fn ahead_by(&self) -> Money {
+        self.projected - self.actual
+    }
+}
+
+/// Parses a date returned by the mortgage API, either ISO formatted (optionally with a time) or
+/// in any of the formats accepted on the command line
+pub(super) fn parse_api_date(input: &str, verbose: bool) -> Result<Date> {
+    let date = input.split(['T', ' ']).next().unwrap_or_default().trim();
+    if let Ok(date) = Date::parse(date, format_description!("[year]-[month]-[day]")) {
+        return Ok(date);
+    }
+
+    Ok(get_date_from_string_arg(Some(date), verbose)
+        .with_context(|| format!("Unable to parse mortgage API date '{input}'"))?
+        .date())
+}
+
+/// Last day of the mortgage term, `term_years` after it started. `None` when the API has no
+/// term for it.
+pub(super) fn term_end(summary: &MortgageSummary, verbose: bool) -> Result<Option<Date>> {
+    if summary.term_years <= 0 {
+        return Ok(None);
+    }
+
+    let start = parse_api_date(&summary.start_date, verbose)?;
+    let end = DateTimeKeeper::new_from_dmy(start.day(), start.month().into(), start.year() as u32)?
+        .apply_year_delta(summary.term_years)?;
+    Ok(end.date().previous_day())
+}
+
+/// Where the simulation picks up: the first of the month after the last payment, or the
+/// mortgage start date when nothing has been paid yet
+pub(super) fn resume_date(summary: &MortgageSummary, verbose: bool) -> Result<DateTimeKeeper> {
+    if summary.last_payment_date.is_empty() {
+        let start = parse_api_date(&summary.start_date, verbose)?;
+        return DateTimeKeeper::new_from_dmy(
+            start.day(),
+            start.month().into(),
+            start.year() as u32,
+        );
+    }
+
+    let last_payment = parse_api_date(&summary.last_payment_date, verbose)?;
+    DateTimeKeeper::new_from_dmy(1, last_payment.month().into(), last_payment.year() as u32)?
+        .next_month()
+}
+
+/// Simulates the mortgage from its start under the original principal and monthly payment to
+/// the last payment date. The current rate is used throughout, as the API doesn't keep a history
+/// of rate changes. Returns `None` when no payments have been made.
+pub(super) fn check_balance(
+    summary: &MortgageSummary,
+    params: &LoanParameters,
+    verbose: bool,
+) -> Result<Option<BalanceCheck>> {
+    if summary.last_payment_date.is_empty() {
+        return Ok(None);
+    }
+
+    let last_payment = parse_api_date(&summary.last_payment_date, verbose)?;
+    let original = LoanParameters {
+        start_date: parse_api_date(&summary.start_date, verbose)?,
+        end_date: last_payment,
+        open_ended: true,
+        principal: summary.initial_principal,
+        monthly_payment: summary.monthly_payment,
+        interest_rate: summary.interest_rate * 100.0,
+        rate_changes: vec![],
+        max_annual_repayment_pct: None,
+        annual_downpayment: None,
+        monthly_overpayment: Money::ZERO,
+        offset: vec![],
+        ..params.clone()
+    };
+    let schedule = calculate_interest_data_for_period(&original);
+
+    Ok(Some(BalanceCheck {
+        date: last_payment,
+        projected: schedule.closing_balance(),
+        actual: summary.current_balance,
+    }))
+}
+
+pub(super) fn render_balance_check(check: &BalanceCheck) -> String {
+    let ahead_by = check.ahead_by();
+    let position = if ahead_by.is_zero() {
+        "on schedule".to_string()
+    } else if ahead_by.is_negative() {
+        format!("{:.2} behind schedule", -ahead_by)
+    } else {
+        format!("{:.2} ahead of schedule", ahead_by)
+    };
+
+    format!(
+        "Original terms projected a balance of {:.2} on {}. The actual balance is {:.2}, {}\n",
+        check.projected, check.date, check.actual, position
+    )
+}
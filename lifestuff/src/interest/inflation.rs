@@ -0,0 +1,136 @@
+use super::savings::{RealSavings, SavingsSchedule};
+use super::schedule::{AmortizationSchedule, RealTerms};
+use crate::csv_utils;
+use crate::dateinfo::get_date_from_string_arg;
+use anyhow::{Context, Result, ensure};
+use lifestuff_types::interest::Interest;
+use lifestuff_types::money::{Money, RoundingMode};
+use std::fs;
+use time::Date;
+
+/// Average days in a year, for compounding a flat inflation rate over part years
+const DAYS_PER_YEAR: f64 = 365.25;
+
+/// Converts amounts on a future date into money at the start of the simulation
+#[derive(Debug, Clone)]
+pub enum Deflator {
+    /// Prices rise at a constant annual rate (%) from the base date
+    Flat { base: Date, rate: f64 },
+    /// Prices follow a CPI series, keyed by the date each index applies from, in date order
+    Index {
+        base: Date,
+        series: Vec<(Date, f64)>,
+    },
+}
+
+impl Deflator {
+    /// Price level on `date` relative to the base date
+    pub fn price_level(&self, date: &Date) -> f64 {
+        match self {
+            Deflator::Flat { base, rate } => {
+                let years = (*date - *base).whole_days() as f64 / DAYS_PER_YEAR;
+                (1.0 + rate / 100.0).powf(years)
+            }
+            Deflator::Index { base, series } => index_on(series, date) / index_on(series, base),
+        }
+    }
+
+    pub fn to_real(&self, amount: Money, date: &Date, rounding: RoundingMode) -> Money {
+        amount
+            .mul_f64(1.0 / self.price_level(date))
+            .round_to_penny(rounding)
+    }
+}
+
+/// The latest index on or before `date`. Dates before the series use its first index, and dates
+/// after it hold the last one.
+fn index_on(series: &[(Date, f64)], date: &Date) -> f64 {
+    series
+        .iter()
+        .take_while(|(from, _)| from <= date)
+        .last()
+        .or(series.first())
+        .map(|(_, index)| *index)
+        .unwrap_or(1.0)
+}
+
+pub(super) fn parse_cpi_series(contents: &str, verbose: bool) -> Result<Vec<(Date, f64)>> {
+    csv_utils::parse_rows(contents, |line| {
+        let (date, index) = line
+            .split_once(',')
+            .with_context(|| format!("expected `dd/mm/yyyy,index` but found '{line}'"))?;
+        let date = get_date_from_string_arg(Some(date.trim()), verbose).context("invalid date")?;
+        let index = index.trim().parse::<f64>().context("invalid index")?;
+        ensure!(
+            index.is_finite() && index > 0.0,
+            "CPI index must be positive, got: {index}"
+        );
+
+        Ok((date.date(), index))
+    })
+}
+
+/// Resolves the flat inflation rate or CPI series file, with prices measured from `start_date`
+pub(super) fn get_deflator(
+    interest_args: &Interest,
+    start_date: &Date,
+    verbose: bool,
+) -> Result<Option<Deflator>> {
+    if let Some(rate) = interest_args.inflation {
+        ensure!(
+            rate.is_finite() && rate > -100.0,
+            "Inflation must be a percentage above -100, got: {rate}"
+        );
+        return Ok(Some(Deflator::Flat {
+            base: *start_date,
+            rate,
+        }));
+    }
+
+    let Some(path) = &interest_args.cpi else {
+        return Ok(None);
+    };
+    let contents = fs::read_to_string(path)
+        .with_context(|| format!("Unable to read CPI series {}", path.display()))?;
+    let mut series = parse_cpi_series(&contents, verbose)?;
+    ensure!(
+        !series.is_empty(),
+        "No CPI indices found in {}",
+        path.display()
+    );
+    series.sort_by_key(|(date, _)| *date);
+
+    Ok(Some(Deflator::Index {
+        base: *start_date,
+        series,
+    }))
+}
+
+/// Adds today's money equivalents to every row of the schedule, as at each payment date
+pub(super) fn deflate_schedule(
+    schedule: &mut AmortizationSchedule,
+    deflator: &Deflator,
+    rounding: RoundingMode,
+) {
+    for row in &mut schedule.rows {
+        row.real = Some(RealTerms {
+            interest: deflator.to_real(row.interest, &row.date, rounding),
+            paid: deflator.to_real(row.payment + row.overpayment, &row.date, rounding),
+            closing_balance: deflator.to_real(row.closing_balance, &row.date, rounding),
+        });
+    }
+}
+
+/// Adds today's money equivalents to every row of the savings projection
+pub(super) fn deflate_savings(
+    schedule: &mut SavingsSchedule,
+    deflator: &Deflator,
+    rounding: RoundingMode,
+) {
+    for row in &mut schedule.rows {
+        row.real = Some(RealSavings {
+            interest: deflator.to_real(row.interest, &row.date, rounding),
+            closing_balance: deflator.to_real(row.closing_balance, &row.date, rounding),
+        });
+    }
+}
@@ -0,0 +1,288 @@
+use super::schedule::serialize_date;
+use super::{LoanParameters, calculate_interest_data_for_period, is_first_of_month};
+use crate::dateinfo::get_date_from_string_arg;
+use anyhow::{Context, Result, ensure};
+use lifestuff_types::interest::OutputFormat;
+use lifestuff_types::interest::montecarlo::MonteCarlo;
+use lifestuff_types::money::Money;
+use serde::Serialize;
+use std::f64::consts::PI;
+use time::Date;
+
+/// Seedable pseudo-random number generator (SplitMix64). Small and fully deterministic, so a
+/// seed reproduces the same rate paths on every platform.
+pub struct SplitMix64(u64);
+
+impl SplitMix64 {
+    pub fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniformly distributed in `[0, 1)`
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Standard normal, using the Box-Muller transform
+    pub fn next_normal(&mut self) -> f64 {
+        let u1 = 1.0 - self.next_f64();
+        let u2 = self.next_f64();
+        (-2.0 * u1.ln()).sqrt() * (2.0 * PI * u2).cos()
+    }
+}
+
+/// The 10th, 50th and 90th percentiles of a measure across the simulated paths
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Percentiles<T> {
+    pub p10: T,
+    pub p50: T,
+    pub p90: T,
+}
+
+impl<T> Percentiles<T> {
+    fn map<U>(self, f: impl Fn(T) -> U) -> Percentiles<U> {
+        Percentiles {
+            p10: f(self.p10),
+            p50: f(self.p50),
+            p90: f(self.p90),
+        }
+    }
+}
+
+/// Nearest-rank percentiles of `values`, which must not be empty
+pub(super) fn percentiles<T: Ord + Copy>(mut values: Vec<T>) -> Percentiles<T> {
+    values.sort();
+    let rank = |pct: usize| values[(pct * values.len()).div_ceil(100).max(1) - 1];
+
+    Percentiles {
+        p10: rank(10),
+        p50: rank(50),
+        p90: rank(90),
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MonteCarloSummary {
+    pub paths: usize,
+    pub seed: u64,
+    #[serde(serialize_with = "serialize_date")]
+    pub end_date: Date,
+    /// `None` where the balance isn't cleared by the end date
+    #[serde(serialize_with = "serialize_payoff_dates")]
+    pub payoff_date: Percentiles<Option<Date>>,
+    /// Across the paths that clear the balance, as the interest on the others is cut short.
+    /// `None` when no path clears it
+    pub total_interest: Option<Percentiles<Money>>,
+    /// Number of paths on which the balance isn't cleared by the end date
+    pub uncleared: usize,
+}
+
+fn serialize_payoff_dates<S: serde::Serializer>(
+    dates: &Percentiles<Option<Date>>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    dates
+        .map(|date| date.map(|date| date.to_string()))
+        .serialize(serializer)
+}
+
+/// Payment dates the rate changes on: each remortgage date, moved to the next payment date when
+/// part way through a month, or every year from the start when none are given
+fn change_dates(params: &LoanParameters, args: &MonteCarlo, verbose: bool) -> Result<Vec<Date>> {
+    if args.remortgage_dates.is_empty() {
+        return Ok((1..)
+            .map_while(|years| {
+                Date::from_calendar_date(
+                    params.start_date.year() + years,
+                    params.start_date.month(),
+                    1,
+                )
+                .ok()
+            })
+            .take_while(|date| *date <= params.end_date)
+            .collect());
+    }
+
+    let mut dates = args
+        .remortgage_dates
+        .iter()
+        .map(|input| {
+            let mut date = get_date_from_string_arg(Some(input), verbose)?;
+            if !is_first_of_month(&date.date()) {
+                date = date.next_month()?;
+            }
+            ensure!(
+                date.date() > params.start_date,
+                "Remortgage on {input} takes effect on {}, which is not after the simulation start of {}",
+                date.date(),
+                params.start_date
+            );
+            Ok(date.date())
+        })
+        .collect::<Result<Vec<Date>>>()?;
+    dates.sort();
+    dates.dedup();
+
+    Ok(dates)
+}
+
+/// Generates one path of rates from the current rate, changing on each of `dates` by a random
+/// shock or a step of the random walk. Rates never go below zero.
+fn rate_path(
+    params: &LoanParameters,
+    dates: &[Date],
+    args: &MonteCarlo,
+    rng: &mut SplitMix64,
+) -> Vec<(Date, f64)> {
+    let mut rate = params.interest_rate;
+    let mut previous = params.start_date;
+
+    dates
+        .iter()
+        .map(|date| {
+            let change = if args.shocks.is_empty() {
+                let years = (*date - previous).whole_days() as f64 / 365.25;
+                rng.next_normal() * args.volatility * years.sqrt()
+            } else {
+                args.shocks[(rng.next_u64() % args.shocks.len() as u64) as usize]
+            };
+            rate = (rate + change).max(0.0);
+            previous = *date;
+            (*date, rate)
+        })
+        .collect()
+}
+
+/// Runs the daily simulator once per rate path and summarises the spread of outcomes. Without
+/// an end date, a path stops as soon as its balance is no longer falling, and counts as never
+/// clearing it. Paths that never clear the balance are left out of the total interest.
+pub fn simulate_paths(
+    params: &LoanParameters,
+    args: &MonteCarlo,
+    verbose: bool,
+) -> Result<MonteCarloSummary> {
+    ensure!(args.paths > 0, "At least one rate path must be simulated");
+    ensure!(
+        args.volatility.is_finite() && args.volatility >= 0.0,
+        "Volatility must be a non-negative number of percentage points, got: {}",
+        args.volatility
+    );
+    ensure!(
+        args.shocks.iter().all(|shock| shock.is_finite()),
+        "Rate shocks must be numbers of percentage points, got: {:?}",
+        args.shocks
+    );
+    ensure!(
+        params.rate_changes.is_empty(),
+        "Rate changes can't be combined with montecarlo, which generates its own. Pass --remortgage dates to it instead"
+    );
+
+    let dates = change_dates(params, args, verbose)?;
+    let mut rng = SplitMix64::new(args.seed);
+    let mut payoff_dates = Vec::with_capacity(args.paths);
+    let mut total_interest = Vec::with_capacity(args.paths);
+    for path in 0..args.paths {
+        let rate_changes = rate_path(params, &dates, args, &mut rng);
+        if verbose {
+            println!("Path {}: {:?}", path + 1, rate_changes);
+        }
+        let schedule = calculate_interest_data_for_period(&LoanParameters {
+            rate_changes,
+            stop_when_growing: true,
+            ..params.clone()
+        });
+        match schedule.payoff_date() {
+            Some(payoff_date) => {
+                payoff_dates.push(payoff_date);
+                total_interest.push(schedule.total_interest());
+            }
+            // Uncleared paths sort after every payoff date
+            None => payoff_dates.push(Date::MAX),
+        }
+    }
+
+    Ok(MonteCarloSummary {
+        paths: args.paths,
+        seed: args.seed,
+        end_date: params.end_date,
+        uncleared: payoff_dates
+            .iter()
+            .filter(|date| **date == Date::MAX)
+            .count(),
+        payoff_date: percentiles(payoff_dates)
+            .map(|date| Some(date).filter(|date| *date != Date::MAX)),
+        total_interest: (!total_interest.is_empty()).then(|| percentiles(total_interest)),
+    })
+}
+
+fn format_payoff_date(date: Option<Date>) -> String {
+    date.map(|date| date.to_string())
+        .unwrap_or_else(|| "not cleared".to_string())
+}
+
+fn render_table(summary: &MonteCarloSummary) -> String {
+    let mut output = format!(
+        "Simulated {} rate paths from seed {}\n{:<16}{:>14}{:>14}{:>14}\n",
+        summary.paths, summary.seed, "", "P10", "P50", "P90"
+    );
+    let payoff = summary.payoff_date.map(format_payoff_date);
+    output.push_str(&format!(
+        "{:<16}{:>14}{:>14}{:>14}\n",
+        "Payoff date", payoff.p10, payoff.p50, payoff.p90
+    ));
+    let interest = summary
+        .total_interest
+        .map(|interest| interest.map(|amount| format!("{amount:.2}")))
+        .unwrap_or_else(|| Percentiles {
+            p10: "-".to_string(),
+            p50: "-".to_string(),
+            p90: "-".to_string(),
+        });
+    output.push_str(&format!(
+        "{:<16}{:>14}{:>14}{:>14}\n",
+        "Total interest", interest.p10, interest.p50, interest.p90
+    ));
+    if summary.uncleared > 0 {
+        output.push_str(&format!(
+            "{} of {} paths don't clear the balance by {}, and are left out of the total interest\n",
+            summary.uncleared, summary.paths, summary.end_date
+        ));
+    }
+
+    output
+}
+
+fn render_csv(summary: &MonteCarloSummary) -> String {
+    let payoff = summary
+        .payoff_date
+        .map(|date| date.map(|date| date.to_string()).unwrap_or_default());
+    let interest = match summary.total_interest {
+        Some(interest) => interest.map(|amount| format!("{amount:.2}")),
+        None => Percentiles {
+            p10: String::new(),
+            p50: String::new(),
+            p90: String::new(),
+        },
+    };
+    format!(
+        "measure,p10,p50,p90\npayoff_date,{},{},{}\ntotal_interest,{},{},{}\n",
+        payoff.p10, payoff.p50, payoff.p90, interest.p10, interest.p50, interest.p90
+    )
+}
+
+pub fn render_summary(summary: &MonteCarloSummary, format: OutputFormat) -> Result<String> {
+    match format {
+        OutputFormat::Table => Ok(render_table(summary)),
+        OutputFormat::Csv => Ok(render_csv(summary)),
+        OutputFormat::Json => serde_json::to_string_pretty(summary)
+            .context("Unable to serialise Monte Carlo summary to JSON"),
+    }
+}
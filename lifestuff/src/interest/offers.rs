@@ -0,0 +1,343 @@
+use super::{
+    LoanParameters, annuity_payment, calculate_interest_data_for_period, payments_remaining,
+};
+use anyhow::{Context, Result, bail, ensure};
+use lifestuff_types::interest::OutputFormat;
+use lifestuff_types::money::Money;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use time::Date;
+
+/// A remortgage offer as read from the offers file
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Offer {
+    pub name: String,
+    /// Annual interest rate (%) over the fixed period
+    pub rate: f64,
+    /// Length of the fixed period in years
+    #[serde(alias = "term_years")]
+    pub term: u8,
+    #[serde(default)]
+    pub product_fee: Money,
+    #[serde(default)]
+    pub valuation_fee: Money,
+    #[serde(default)]
+    pub cashback: Money,
+    /// The product fee is added to the loan rather than paid upfront
+    #[serde(default)]
+    pub fee_added_to_loan: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct OffersFile {
+    #[serde(rename = "offer")]
+    offers: Vec<Offer>,
+}
+
+/// Cost of taking an offer over its fixed period
+#[derive(Debug, Clone, Serialize)]
+pub struct OfferSummary {
+    pub name: String,
+    pub rate: f64,
+    pub term: u8,
+    pub monthly_payment: Money,
+    pub interest: Money,
+    /// Fees less cashback
+    pub net_fees: Money,
+    /// Interest plus fees less cashback
+    pub total_cost: Money,
+    pub balance_at_end: Money,
+    /// Annual percentage rate of charge (%) over the fixed period, including fees and cashback
+    pub aprc: f64,
+}
+
+impl OfferSummary {
+    pub fn cost_per_year(&self) -> Money {
+        self.total_cost.mul_f64(1.0 / f64::from(self.term))
+    }
+}
+
+fn parse_offers_toml(contents: &str) -> Result<Vec<Offer>> {
+    Ok(toml::from_str::<OffersFile>(contents)
+        .context("Unable to parse offers. Expected [[offer]] tables")?
+        .offers)
+}
+
+/// Parses offers from CSV with a header row naming the columns, in any order
+fn parse_offers_csv(contents: &str) -> Result<Vec<Offer>> {
+    let mut lines = contents
+        .lines()
+        .enumerate()
+        .map(|(line_number, line)| (line_number, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'));
+    let (_, header) = lines.next().context("No header row found in offers CSV")?;
+    let columns = header.split(',').map(str::trim).collect::<Vec<&str>>();
+    for required in ["name", "rate", "term"] {
+        ensure!(
+            columns.contains(&required),
+            "Offers CSV header is missing the `{required}` column"
+        );
+    }
+
+    lines
+        .map(|(line_number, line)| {
+            let fields = line.split(',').map(str::trim).collect::<Vec<&str>>();
+            ensure!(
+                fields.len() == columns.len(),
+                "Line {}: expected {} fields but found {}",
+                line_number + 1,
+                columns.len(),
+                fields.len()
+            );
+            let field = |column: &str| {
+                columns
+                    .iter()
+                    .position(|name| *name == column)
+                    .map(|index| fields[index])
+                    .filter(|value| !value.is_empty())
+            };
+            let money = |column: &str| -> Result<Money> {
+                field(column)
+                    .map(|value| value.parse::<Money>().map_err(anyhow::Error::msg))
+                    .transpose()
+                    .map(Option::unwrap_or_default)
+                    .context(format!("Line {}: invalid {column}", line_number + 1))
+            };
+
+            Ok(Offer {
+                name: field("name").unwrap_or_default().to_string(),
+                rate: field("rate")
+                    .unwrap_or_default()
+                    .trim_end_matches('%')
+                    .parse()
+                    .context(format!("Line {}: invalid rate", line_number + 1))?,
+                term: field("term")
+                    .unwrap_or_default()
+                    .parse()
+                    .context(format!("Line {}: invalid term", line_number + 1))?,
+                product_fee: money("product_fee")?,
+                valuation_fee: money("valuation_fee")?,
+                cashback: money("cashback")?,
+                fee_added_to_loan: field("fee_added_to_loan")
+                    .map(|value| value.parse::<bool>())
+                    .transpose()
+                    .context(format!(
+                        "Line {}: invalid fee_added_to_loan",
+                        line_number + 1
+                    ))?
+                    .unwrap_or_default(),
+            })
+        })
+        .collect()
+}
+
+/// Reads offers from a `.csv` file, or TOML otherwise
+pub(super) fn parse_offers(path: &Path, contents: &str) -> Result<Vec<Offer>> {
+    let offers = match path.extension().and_then(|extension| extension.to_str()) {
+        Some(extension) if extension.eq_ignore_ascii_case("csv") => parse_offers_csv(contents)?,
+        _ => parse_offers_toml(contents)?,
+    };
+    ensure!(!offers.is_empty(), "No offers found in {}", path.display());
+    for offer in &offers {
+        ensure!(
+            offer.rate.is_finite() && offer.rate >= 0.0,
+            "Offer '{}' has an invalid rate of {}",
+            offer.name,
+            offer.rate
+        );
+        ensure!(
+            offer.term > 0,
+            "Offer '{}' has a term of 0 years",
+            offer.name
+        );
+        ensure!(
+            !offer.product_fee.is_negative()
+                && !offer.valuation_fee.is_negative()
+                && !offer.cashback.is_negative(),
+            "Offer '{}' has a negative fee or cashback",
+            offer.name
+        );
+    }
+
+    Ok(offers)
+}
+
+/// Annual rate (%) that discounts the monthly `payments`, made one month apart from a month after
+/// the advance, back to `advance`. Found by bisection on the monthly rate.
+pub(super) fn annual_percentage_rate(advance: Money, payments: &[Money]) -> f64 {
+    let present_value = |monthly_rate: f64| {
+        payments
+            .iter()
+            .enumerate()
+            .map(|(month, payment)| payment.to_f64() / (1.0 + monthly_rate).powi(month as i32 + 1))
+            .sum::<f64>()
+    };
+
+    let (mut low, mut high) = (-0.5, 1.0);
+    for _ in 0..200 {
+        let mid = (low + high) / 2.0;
+        if present_value(mid) > advance.to_f64() {
+            low = mid;
+        } else {
+            high = mid;
+        }
+    }
+
+    ((1.0 + (low + high) / 2.0).powi(12) - 1.0) * 100.0
+}
+
+/// Simulates each offer over its fixed period and ranks them by APRC, cheapest first. With an
+/// end date the payment is the one that clears the loan by then at the offer's rate, otherwise
+/// `--repayment` is used. The balance left at the end of the fixed period is treated as repaid
+/// then, so the APRC covers only the deal itself.
+pub fn compare_offers(params: &LoanParameters, offers: &[Offer]) -> Result<Vec<OfferSummary>> {
+    if params.open_ended && !params.monthly_payment.is_positive() {
+        bail!("Pass --end-date or --repayment to work out the monthly payment for each offer");
+    }
+
+    let mut summaries = offers
+        .iter()
+        .map(|offer| {
+            let principal = if offer.fee_added_to_loan {
+                params.principal + offer.product_fee
+            } else {
+                params.principal
+            };
+            let monthly_payment = if params.open_ended {
+                params.monthly_payment
+            } else {
+                annuity_payment(
+                    principal,
+                    offer.rate,
+                    payments_remaining(&params.start_date, &params.end_date),
+                    params.rounding,
+                )
+            };
+            let fixed_end = Date::from_calendar_date(
+                params.start_date.year() + i32::from(offer.term),
+                params.start_date.month(),
+                1,
+            )
+            .context("Offer term is out of range")?
+            .previous_day()
+            .unwrap();
+
+            let schedule = calculate_interest_data_for_period(&LoanParameters {
+                end_date: fixed_end.min(params.end_date),
+                open_ended: false,
+                principal,
+                monthly_payment,
+                interest_rate: offer.rate,
+                rate_changes: vec![],
+                ..params.clone()
+            });
+
+            let upfront_fees = offer.valuation_fee
+                + if offer.fee_added_to_loan {
+                    Money::ZERO
+                } else {
+                    offer.product_fee
+                };
+            let net_fees = offer.product_fee + offer.valuation_fee - offer.cashback;
+            let mut payments = schedule
+                .rows
+                .iter()
+                .map(|row| row.payment + row.overpayment)
+                .collect::<Vec<Money>>();
+            if let Some(last) = payments.last_mut() {
+                *last += schedule.closing_balance();
+            }
+
+            Ok(OfferSummary {
+                name: offer.name.clone(),
+                rate: offer.rate,
+                term: offer.term,
+                monthly_payment,
+                interest: schedule.total_interest(),
+                net_fees,
+                total_cost: schedule.total_interest() + net_fees,
+                balance_at_end: schedule.closing_balance(),
+                aprc: annual_percentage_rate(
+                    params.principal - upfront_fees + offer.cashback,
+                    &payments,
+                ),
+            })
+        })
+        .collect::<Result<Vec<OfferSummary>>>()?;
+    summaries.sort_by(|a, b| a.aprc.total_cmp(&b.aprc));
+
+    Ok(summaries)
+}
+
+fn render_table(summaries: &[OfferSummary]) -> String {
+    let mut output = format!(
+        "{:<5}{:<24}{:>8}{:>6}{:>12}{:>14}{:>12}{:>14}{:>12}{:>8}\n",
+        "Rank",
+        "Offer",
+        "Rate",
+        "Term",
+        "Payment",
+        "Interest",
+        "Net fees",
+        "Total cost",
+        "Per year",
+        "APRC"
+    );
+    for (rank, summary) in summaries.iter().enumerate() {
+        output.push_str(&format!(
+            "{:<5}{:<24}{:>7.2}%{:>5}y{:>12.2}{:>14.2}{:>12.2}{:>14.2}{:>12.2}{:>7.2}%\n",
+            rank + 1,
+            summary.name,
+            summary.rate,
+            summary.term,
+            summary.monthly_payment,
+            summary.interest,
+            summary.net_fees,
+            summary.total_cost,
+            summary.cost_per_year(),
+            summary.aprc
+        ));
+    }
+
+    output
+}
+
+fn render_csv(summaries: &[OfferSummary]) -> String {
+    let mut output = "rank,offer,rate,term,monthly_payment,interest,net_fees,total_cost,cost_per_year,balance_at_end,aprc\n".to_string();
+    for (rank, summary) in summaries.iter().enumerate() {
+        output.push_str(&format!(
+            "{},{},{:.2},{},{:.2},{:.2},{:.2},{:.2},{:.2},{:.2},{:.2}\n",
+            rank + 1,
+            summary.name,
+            summary.rate,
+            summary.term,
+            summary.monthly_payment,
+            summary.interest,
+            summary.net_fees,
+            summary.total_cost,
+            summary.cost_per_year(),
+            summary.balance_at_end,
+            summary.aprc
+        ));
+    }
+
+    output
+}
+
+pub fn render_offers(summaries: &[OfferSummary], format: OutputFormat) -> Result<String> {
+    match format {
+        OutputFormat::Table => Ok(render_table(summaries)),
+        OutputFormat::Csv => Ok(render_csv(summaries)),
+        OutputFormat::Json => serde_json::to_string_pretty(summaries)
+            .context("Unable to serialise offer comparison to JSON"),
+    }
+}
+
+/// Loads the offers file and compares the offers in it
+pub fn load_and_compare(params: &LoanParameters, path: &Path) -> Result<Vec<OfferSummary>> {
+    let contents = fs::read_to_string(path)
+        .with_context(|| format!("Unable to read offers file {}", path.display()))?;
+
+    compare_offers(params, &parse_offers(path, &contents)?)
+}
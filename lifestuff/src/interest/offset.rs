@@ -0,0 +1,140 @@
+use super::schedule::AmortizationSchedule;
+use super::{LoanParameters, calculate_interest_data_for_period, daily_year_fraction};
+use crate::csv_utils;
+use crate::dateinfo::get_date_from_string_arg;
+use anyhow::{Context, Result, ensure};
+use lifestuff_types::interest::Interest;
+use lifestuff_types::money::Money;
+use std::fs;
+use time::Date;
+
+/// How the offset savings compare with making no offset and holding them separately instead
+#[derive(Debug)]
+pub struct OffsetSummary {
+    /// Interest charged as an annual rate (%) on the whole balance
+    pub effective_rate: f64,
+    /// Interest saved compared with the same loan without an offset
+    pub interest_saved: Money,
+    /// Rate (%) the savings would earn if held separately
+    pub savings_rate: f64,
+    /// Interest the savings would earn if held separately, before tax
+    pub savings_interest: Money,
+}
+
+impl OffsetSummary {
+    pub fn net_benefit(&self) -> Money {
+        self.interest_saved - self.savings_interest
+    }
+}
+
+pub(super) fn parse_offset_schedule(contents: &str, verbose: bool) -> Result<Vec<(Date, Money)>> {
+    csv_utils::parse_rows(contents, |line| {
+        let (date, balance) = line
+            .split_once(',')
+            .with_context(|| format!("expected `dd/mm/yyyy,balance` but found '{line}'"))?;
+        let date = get_date_from_string_arg(Some(date.trim()), verbose).context("invalid date")?;
+        let balance = balance
+            .trim()
+            .parse::<Money>()
+            .map_err(anyhow::Error::msg)
+            .context("invalid balance")?;
+        ensure!(
+            !balance.is_negative(),
+            "offset balance must not be negative, got: {balance}"
+        );
+
+        Ok((date.date(), balance))
+    })
+}
+
+/// Resolves the constant offset or the dated offset schedule into balances keyed by the date
+/// they apply from. Before the first dated balance nothing is offset.
+pub fn get_offset_balances(
+    interest_args: &Interest,
+    start_date: &Date,
+    verbose: bool,
+) -> Result<Vec<(Date, Money)>> {
+    if let Some(balance) = interest_args.offset {
+        return Ok(vec![(*start_date, balance)]);
+    }
+
+    let Some(path) = &interest_args.offset_schedule else {
+        return Ok(vec![]);
+    };
+    let contents = fs::read_to_string(path)
+        .with_context(|| format!("Unable to read offset schedule {}", path.display()))?;
+    let mut balances = parse_offset_schedule(&contents, verbose)?;
+    ensure!(
+        !balances.is_empty(),
+        "No offset balances found in {}",
+        path.display()
+    );
+    balances.sort_by_key(|(date, _)| *date);
+
+    Ok(balances)
+}
+
+/// Simple interest the offset savings would earn between the start date and `until` (exclusive)
+fn savings_interest(params: &LoanParameters, until: Date, savings_rate: f64) -> Money {
+    let mut balances = params.offset.iter().peekable();
+    let mut balance = Money::ZERO;
+    let mut interest = Money::ZERO;
+    let mut current_date = params.start_date;
+
+    while current_date < until {
+        while let Some((_, new_balance)) = balances.next_if(|(from, _)| *from <= current_date) {
+            balance = *new_balance;
+        }
+        interest += balance
+            .mul_f64(savings_rate / 100.0 * daily_year_fraction(&current_date, params.day_count));
+        current_date = current_date.next_day().unwrap();
+    }
+
+    interest.round_to_penny(params.rounding)
+}
+
+pub fn summarise_offset(
+    params: &LoanParameters,
+    schedule: &AmortizationSchedule,
+    savings_rate: f64,
+) -> OffsetSummary {
+    let without_offset = calculate_interest_data_for_period(&LoanParameters {
+        offset: vec![],
+        ..params.clone()
+    });
+
+    // The savings are only tied up until the loan is cleared
+    let until = schedule
+        .payoff_date()
+        .unwrap_or_else(|| params.end_date.next_day().unwrap());
+    let effective_rate = if schedule.balance_years > 0.0 {
+        schedule.total_interest().to_f64() / schedule.balance_years * 100.0
+    } else {
+        0.0
+    };
+
+    OffsetSummary {
+        effective_rate,
+        interest_saved: without_offset.total_interest() - schedule.total_interest(),
+        savings_rate,
+        savings_interest: savings_interest(params, until, savings_rate),
+    }
+}
+
+pub fn render_offset_summary(summary: &OffsetSummary) -> String {
+    let net_benefit = summary.net_benefit();
+    format!(
+        "Offsetting savings gives an effective rate of {:.2}% and saves {:.2} interest compared with no offset\n\
+         Held separately at {:.2}% the savings would earn {:.2} before tax, so offsetting is {:.2} {}\n",
+        summary.effective_rate,
+        summary.interest_saved,
+        summary.savings_rate,
+        summary.savings_interest,
+        net_benefit.abs(),
+        if net_benefit.is_negative() {
+            "worse off"
+        } else {
+            "better off"
+        }
+    )
+}
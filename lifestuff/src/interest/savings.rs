@@ -0,0 +1,335 @@
+use super::schedule::serialize_date;
+use super::{LoanParameters, daily_year_fraction, is_first_of_month};
+use anyhow::{Context, Result, ensure};
+use lifestuff_types::interest::OutputFormat;
+use lifestuff_types::interest::savings::{Savings, SavingsCompounding};
+use lifestuff_types::money::{Money, RoundingMode};
+use serde::Serialize;
+use time::{Date, Month};
+
+/// Annual ISA subscription limit
+const ISA_ALLOWANCE: Money = Money::from_major(20_000);
+
+/// One month of a savings projection, starting on its contribution date
+#[derive(Debug, Clone, Serialize)]
+pub struct SavingsRow {
+    #[serde(serialize_with = "serialize_date")]
+    pub date: Date,
+    pub opening_balance: Money,
+    pub contribution: Money,
+    pub interest: Money,
+    pub tax: Money,
+    pub closing_balance: Money,
+    /// The row in today's money, when an inflation assumption was given
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub real: Option<RealSavings>,
+}
+
+/// Amounts from a savings row expressed in money at the start of the projection
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct RealSavings {
+    pub interest: Money,
+    pub closing_balance: Money,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SavingsSchedule {
+    pub rows: Vec<SavingsRow>,
+}
+
+impl SavingsSchedule {
+    pub fn total_contributions(&self) -> Money {
+        self.rows.iter().map(|row| row.contribution).sum()
+    }
+
+    pub fn total_interest(&self) -> Money {
+        self.rows.iter().map(|row| row.interest).sum()
+    }
+
+    pub fn total_tax(&self) -> Money {
+        self.rows.iter().map(|row| row.tax).sum()
+    }
+
+    pub fn closing_balance(&self) -> Money {
+        self.rows
+            .last()
+            .map(|row| row.closing_balance)
+            .unwrap_or_default()
+    }
+
+    /// Total interest and the closing balance in today's money, when the projection has been
+    /// deflated
+    pub fn real_totals(&self) -> Option<(Money, Money)> {
+        let interest = self.rows.iter().try_fold(Money::ZERO, |interest, row| {
+            row.real.map(|real| interest + real.interest)
+        })?;
+        let closing_balance = self.rows.last()?.real?.closing_balance;
+
+        Some((interest, closing_balance))
+    }
+}
+
+/// UK tax years run from 6 April, and are identified by the year they start in
+pub(super) fn tax_year(date: &Date) -> i32 {
+    if (date.month(), date.day()) >= (Month::April, 6) {
+        date.year()
+    } else {
+        date.year() - 1
+    }
+}
+
+fn months_between_credits(compounding: SavingsCompounding) -> usize {
+    match compounding {
+        SavingsCompounding::Daily | SavingsCompounding::Monthly => 1,
+        SavingsCompounding::Quarterly => 3,
+        SavingsCompounding::Annually => 12,
+    }
+}
+
+/// Running state of the pot, including what has been paid in and earned in the current tax year
+struct Pot {
+    balance: Money,
+    accrued: Money,
+    tax_year: i32,
+    contributed_this_year: Money,
+    interest_this_year: Money,
+    annual_cap: Option<Money>,
+    tax_rate: Option<f64>,
+    allowance: Money,
+    rounding: RoundingMode,
+}
+
+impl Pot {
+    fn roll_tax_year(&mut self, date: &Date) {
+        if tax_year(date) != self.tax_year {
+            self.tax_year = tax_year(date);
+            self.contributed_this_year = Money::ZERO;
+            self.interest_this_year = Money::ZERO;
+        }
+    }
+
+    /// Pays in up to `amount`, limited by what is left of the annual cap
+    fn contribute(&mut self, amount: Money) -> Money {
+        let contribution = match self.annual_cap {
+            Some(cap) => amount
+                .min(cap - self.contributed_this_year)
+                .max(Money::ZERO),
+            None => amount,
+        };
+        self.contributed_this_year += contribution;
+        self.balance += contribution;
+        contribution
+    }
+
+    /// Credits the accrued interest to the nearest penny, less tax on whatever takes this tax
+    /// year's interest over the allowance. Returns the interest and tax.
+    fn credit_interest(&mut self) -> (Money, Money) {
+        let interest = std::mem::take(&mut self.accrued).round_to_penny(self.rounding);
+        let tax = match self.tax_rate {
+            Some(rate) => {
+                let taxable_before = (self.interest_this_year - self.allowance).max(Money::ZERO);
+                let taxable_after =
+                    (self.interest_this_year + interest - self.allowance).max(Money::ZERO);
+                (taxable_after - taxable_before).percentage(rate, self.rounding)
+            }
+            None => Money::ZERO,
+        };
+
+        self.interest_this_year += interest;
+        self.balance += interest - tax;
+        (interest, tax)
+    }
+}
+
+/// Projects a savings pot with the same day by day loop as the loan simulator: contributions are
+/// paid in on the first of each month, interest accrues daily under the day-count convention and
+/// is credited monthly, quarterly or annually from the start date, and at the end date
+pub fn project_savings(params: &LoanParameters, savings_args: &Savings) -> Result<SavingsSchedule> {
+    ensure!(
+        !params.open_ended,
+        "An --end-date is needed to project savings"
+    );
+
+    let mut pot = Pot {
+        balance: params.principal,
+        accrued: Money::ZERO,
+        tax_year: tax_year(&params.start_date),
+        contributed_this_year: Money::ZERO,
+        interest_this_year: Money::ZERO,
+        annual_cap: match savings_args.isa {
+            true => Some(ISA_ALLOWANCE),
+            false => savings_args.annual_cap,
+        },
+        tax_rate: match savings_args.isa {
+            true => None,
+            false => savings_args.tax_rate,
+        },
+        allowance: savings_args.allowance,
+        rounding: params.rounding,
+    };
+    let credit_every = months_between_credits(savings_args.compounding);
+    let mut interest_rate = params.interest_rate;
+    let mut rate_changes = params.rate_changes.iter().peekable();
+    let mut schedule = SavingsSchedule::default();
+    let mut current_row: Option<SavingsRow> = None;
+    let mut current_date = params.start_date;
+
+    let close_row = |mut row: SavingsRow, pot: &mut Pot, credit: bool| {
+        if credit {
+            (row.interest, row.tax) = pot.credit_interest();
+        }
+        row.closing_balance = pot.balance;
+        row
+    };
+
+    while current_date <= params.end_date {
+        pot.roll_tax_year(&current_date);
+
+        if is_first_of_month(&current_date) || current_row.is_none() {
+            if let Some(row) = current_row.take() {
+                let credit = (schedule.rows.len() + 1).is_multiple_of(credit_every);
+                schedule.rows.push(close_row(row, &mut pot, credit));
+            }
+            current_row = Some(SavingsRow {
+                date: current_date,
+                opening_balance: pot.balance,
+                contribution: Money::ZERO,
+                interest: Money::ZERO,
+                tax: Money::ZERO,
+                closing_balance: pot.balance,
+                real: None,
+            });
+        }
+        let row = current_row.as_mut().unwrap();
+
+        if is_first_of_month(&current_date) {
+            if let Some((_, new_rate)) =
+                rate_changes.next_if(|(effective, _)| *effective <= current_date)
+            {
+                interest_rate = *new_rate;
+            }
+            row.contribution = pot.contribute(savings_args.contribution);
+        }
+
+        let daily_rate =
+            interest_rate / 100.0 * daily_year_fraction(&current_date, params.day_count);
+        pot.accrued += match savings_args.compounding {
+            SavingsCompounding::Daily => (pot.balance + pot.accrued).mul_f64(daily_rate),
+            _ => pot.balance.mul_f64(daily_rate),
+        };
+
+        current_date = current_date.next_day().unwrap();
+    }
+
+    if let Some(row) = current_row {
+        schedule.rows.push(close_row(row, &mut pot, true));
+    }
+
+    Ok(schedule)
+}
+
+const CSV_HEADER: &str = "date,opening_balance,contribution,interest,tax,closing_balance";
+
+fn render_table(schedule: &SavingsSchedule) -> String {
+    let real_totals = schedule.real_totals();
+    let mut output = format!(
+        "{:<12}{:>14}{:>14}{:>12}{:>10}{:>14}",
+        "Date", "Opening", "Contribution", "Interest", "Tax", "Closing"
+    );
+    if real_totals.is_some() {
+        output.push_str(&format!("{:>15}{:>14}", "Real interest", "Real closing"));
+    }
+    output.push('\n');
+    for row in &schedule.rows {
+        output.push_str(&format!(
+            "{:<12}{:>14.2}{:>14.2}{:>12.2}{:>10.2}{:>14.2}",
+            row.date.to_string(),
+            row.opening_balance,
+            row.contribution,
+            row.interest,
+            row.tax,
+            row.closing_balance
+        ));
+        if let Some(real) = row.real {
+            output.push_str(&format!(
+                "{:>15.2}{:>14.2}",
+                real.interest, real.closing_balance
+            ));
+        }
+        output.push('\n');
+    }
+    output.push_str(&format!(
+        "Balance after {} months is {:.2}. Contributed {:.2} and earned {:.2} interest, less {:.2} tax\n",
+        schedule.rows.len(),
+        schedule.closing_balance(),
+        schedule.total_contributions(),
+        schedule.total_interest(),
+        schedule.total_tax()
+    ));
+    if let Some((interest, closing_balance)) = real_totals {
+        output.push_str(&format!(
+            "In today's money the balance is {:.2} and the interest earned {:.2}\n",
+            closing_balance, interest
+        ));
+    }
+
+    output
+}
+
+fn render_csv(schedule: &SavingsSchedule) -> String {
+    let mut output = CSV_HEADER.to_string();
+    if schedule.real_totals().is_some() {
+        output.push_str(",real_interest,real_closing_balance");
+    }
+    output.push('\n');
+    for row in &schedule.rows {
+        output.push_str(&format!(
+            "{},{:.2},{:.2},{:.2},{:.2},{:.2}",
+            row.date,
+            row.opening_balance,
+            row.contribution,
+            row.interest,
+            row.tax,
+            row.closing_balance
+        ));
+        if let Some(real) = row.real {
+            output.push_str(&format!(
+                ",{:.2},{:.2}",
+                real.interest, real.closing_balance
+            ));
+        }
+        output.push('\n');
+    }
+
+    output
+}
+
+#[derive(Serialize)]
+struct JsonSavings<'a> {
+    rows: &'a [SavingsRow],
+    total_contributions: Money,
+    total_interest: Money,
+    total_tax: Money,
+    closing_balance: Money,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    real_total_interest: Option<Money>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    real_closing_balance: Option<Money>,
+}
+
+pub fn render_savings(schedule: &SavingsSchedule, format: OutputFormat) -> Result<String> {
+    match format {
+        OutputFormat::Table => Ok(render_table(schedule)),
+        OutputFormat::Csv => Ok(render_csv(schedule)),
+        OutputFormat::Json => serde_json::to_string_pretty(&JsonSavings {
+            rows: &schedule.rows,
+            total_contributions: schedule.total_contributions(),
+            total_interest: schedule.total_interest(),
+            total_tax: schedule.total_tax(),
+            closing_balance: schedule.closing_balance(),
+            real_total_interest: schedule.real_totals().map(|(interest, _)| interest),
+            real_closing_balance: schedule.real_totals().map(|(_, balance)| balance),
+        })
+        .context("Unable to serialise savings projection to JSON"),
+    }
+}
@@ -0,0 +1,162 @@
+use anyhow::{Context, Result, anyhow, ensure};
+use lifestuff_types::interest::Interest;
+use std::fs;
+use std::path::Path;
+
+fn is_json(path: &Path) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("json"))
+}
+
+/// Parses a scenario from JSON for a `.json` file, or TOML otherwise. Relative file paths in it
+/// are taken from the scenario's directory.
+pub(super) fn parse_scenario(path: &Path, contents: &str) -> Result<Interest> {
+    let invalid = |error: &dyn std::fmt::Display| {
+        anyhow!("Unable to parse scenario {}: {error}", path.display())
+    };
+    let mut scenario: Interest = if is_json(path) {
+        serde_json::from_str(contents).map_err(|error| invalid(&error))?
+    } else {
+        toml::from_str(contents).map_err(|error| invalid(&error))?
+    };
+
+    ensure!(
+        scenario.offset.is_none() || scenario.offset_schedule.is_none(),
+        "Scenario {} can't set both offset and offset_schedule",
+        path.display()
+    );
+    ensure!(
+        scenario.inflation.is_none() || scenario.cpi.is_none(),
+        "Scenario {} can't set both inflation and cpi",
+        path.display()
+    );
+
+    let directory = path.parent().unwrap_or(Path::new(""));
+    for file in [
+        &mut scenario.rate_schedule,
+        &mut scenario.offset_schedule,
+        &mut scenario.cpi,
+    ]
+    .into_iter()
+    .flatten()
+    {
+        if file.is_relative() {
+            *file = directory.join(&*file);
+        }
+    }
+
+    Ok(scenario)
+}
+
+/// `cli` when changed from the default, otherwise `scenario`
+fn changed_or<T: PartialEq>(cli: T, scenario: T, default: T) -> T {
+    if cli != default { cli } else { scenario }
+}
+
+/// Options passed on the command line take precedence over the scenario's. Options with a
+/// default only take precedence when changed from it, as clap doesn't report whether they were
+/// passed. Alternatives such as `--offset` and `--offset-schedule` are replaced together, so
+/// either can override the other. `--from-mortgage` is a flag with no way to turn it off, so it
+/// is set when either the command line or the scenario sets it.
+pub(super) fn merge(scenario: Interest, cli: Interest) -> Interest {
+    let defaults = Interest::default();
+    let (offset, offset_schedule) = if cli.offset.is_some() || cli.offset_schedule.is_some() {
+        (cli.offset, cli.offset_schedule)
+    } else {
+        (scenario.offset, scenario.offset_schedule)
+    };
+    let (inflation, cpi) = if cli.inflation.is_some() || cli.cpi.is_some() {
+        (cli.inflation, cli.cpi)
+    } else {
+        (scenario.inflation, scenario.cpi)
+    };
+
+    Interest {
+        operation: cli.operation,
+        scenario: cli.scenario,
+        save_scenario: cli.save_scenario,
+        principal: cli.principal.or(scenario.principal),
+        interest_rate: cli.interest_rate.or(scenario.interest_rate),
+        rate_changes: if cli.rate_changes.is_empty() {
+            scenario.rate_changes
+        } else {
+            cli.rate_changes
+        },
+        rate_schedule: cli.rate_schedule.or(scenario.rate_schedule),
+        repayment: cli.repayment.or(scenario.repayment),
+        max_repayment_pct: cli.max_repayment_pct.or(scenario.max_repayment_pct),
+        annual_downpayment: cli.annual_downpayment.or(scenario.annual_downpayment),
+        anniversary: cli.anniversary.or(scenario.anniversary),
+        early_repayment_charges: if cli.early_repayment_charges.is_empty() {
+            scenario.early_repayment_charges
+        } else {
+            cli.early_repayment_charges
+        },
+        offset,
+        offset_schedule,
+        savings_rate: changed_or(
+            cli.savings_rate,
+            scenario.savings_rate,
+            defaults.savings_rate,
+        ),
+        inflation,
+        cpi,
+        end_date: cli.end_date.or(scenario.end_date),
+        term: cli.term.or(scenario.term),
+        format: changed_or(cli.format, scenario.format, defaults.format),
+        rounding: changed_or(cli.rounding, scenario.rounding, defaults.rounding),
+        convention: changed_or(cli.convention, scenario.convention, defaults.convention),
+        day_count: changed_or(cli.day_count, scenario.day_count, defaults.day_count),
+        from_mortgage: cli.from_mortgage || scenario.from_mortgage,
+        endpoint: cli.endpoint.or(scenario.endpoint),
+    }
+}
+
+/// Writes the options as JSON for a `.json` file, or TOML otherwise. File paths are made
+/// absolute so the scenario can be saved anywhere.
+pub(super) fn render_scenario(path: &Path, interest_args: &Interest) -> Result<String> {
+    let mut scenario = interest_args.clone();
+    for file in [
+        &mut scenario.rate_schedule,
+        &mut scenario.offset_schedule,
+        &mut scenario.cpi,
+    ]
+    .into_iter()
+    .flatten()
+    {
+        *file = std::path::absolute(&*file)
+            .with_context(|| format!("Unable to resolve {}", file.display()))?;
+    }
+
+    if is_json(path) {
+        serde_json::to_string_pretty(&scenario).context("Unable to serialise scenario to JSON")
+    } else {
+        toml::to_string(&scenario).context("Unable to serialise scenario to TOML")
+    }
+}
+
+/// Applies the `--scenario` file under the command line options, then saves the result to
+/// `--save-scenario` if given
+pub(super) fn resolve(interest_args: Interest, verbose: bool) -> Result<Interest> {
+    let interest_args = match &interest_args.scenario {
+        Some(path) => {
+            let contents = fs::read_to_string(path)
+                .with_context(|| format!("Unable to read scenario {}", path.display()))?;
+            let scenario = parse_scenario(path, &contents)?;
+            if verbose {
+                println!("Scenario: {:?}", scenario);
+            }
+            merge(scenario, interest_args)
+        }
+        None => interest_args,
+    };
+
+    if let Some(path) = &interest_args.save_scenario {
+        fs::write(path, render_scenario(path, &interest_args)?)
+            .with_context(|| format!("Unable to save scenario to {}", path.display()))?;
+        eprintln!("Saved scenario to {}", path.display());
+    }
+
+    Ok(interest_args)
+}
@@ -0,0 +1,403 @@
+use anyhow::{Context, Result};
+use lifestuff_types::interest::OutputFormat;
+use lifestuff_types::money::{Money, RoundingMode};
+use serde::{Serialize, Serializer};
+use time::Date;
+
+pub fn serialize_date<S: Serializer>(date: &Date, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(date)
+}
+
+pub fn serialize_optional_date<S: Serializer>(
+    date: &Option<Date>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match date {
+        Some(date) => serializer.collect_str(date),
+        None => serializer.serialize_none(),
+    }
+}
+
+/// One month of the amortization schedule, starting on its payment date
+#[derive(Debug, Clone, Serialize)]
+pub struct ScheduleRow {
+    #[serde(serialize_with = "serialize_date")]
+    pub date: Date,
+    pub opening_balance: Money,
+    pub interest: Money,
+    pub payment: Money,
+    pub overpayment: Money,
+    pub closing_balance: Money,
+    /// The row in today's money, when an inflation assumption was given
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub real: Option<RealTerms>,
+}
+
+/// Amounts from a schedule row expressed in money at the start of the simulation
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct RealTerms {
+    pub interest: Money,
+    /// Payment and overpayment together
+    pub paid: Money,
+    pub closing_balance: Money,
+}
+
+/// A stretch of the loan at a single interest rate, e.g. a fixed period followed by the SVR
+#[derive(Debug, Clone, Serialize)]
+pub struct RatePhase {
+    #[serde(serialize_with = "serialize_date")]
+    pub start: Date,
+    /// Annual interest rate (%)
+    pub rate: f64,
+    pub payment: Money,
+    /// Change in the monthly payment from the previous phase
+    pub payment_change: Money,
+    pub interest: Money,
+}
+
+/// The year between two completion anniversaries, over which overpayments are limited to a
+/// percentage of the balance at the start of it
+#[derive(Debug, Clone, Serialize)]
+pub struct AllowancePeriod {
+    #[serde(serialize_with = "serialize_date")]
+    pub start: Date,
+    #[serde(serialize_with = "serialize_date")]
+    pub resets: Date,
+    pub allowance: Money,
+    pub overpaid: Money,
+    /// Allowance left to overpay before it resets, negative once it has been exceeded
+    pub headroom: Money,
+    /// Early repayment charge (%) on overpayments above the allowance
+    pub charge_pct: f64,
+    pub charge: Money,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct AmortizationSchedule {
+    pub rows: Vec<ScheduleRow>,
+    pub phases: Vec<RatePhase>,
+    pub allowance_periods: Vec<AllowancePeriod>,
+    /// Sum of the daily balances weighted by the fraction of a year each day is, so that
+    /// interest can be expressed as an effective annual rate on the whole balance
+    #[serde(skip)]
+    pub balance_years: f64,
+    /// The projection was stopped early as the balance wasn't falling with nothing left to change
+    #[serde(skip)]
+    pub diverging: bool,
+    /// An annual or monthly overpayment was asked for, so the allowance is worth reporting
+    #[serde(skip)]
+    pub overpaying: bool,
+    /// Date the remaining allowance is reported on. The schedule's start when not set
+    #[serde(skip)]
+    pub today: Option<Date>,
+}
+
+impl AmortizationSchedule {
+    pub fn start_phase(&mut self, start: Date, rate: f64, payment: Money) {
+        let payment_change = self
+            .phases
+            .last()
+            .map(|phase| payment - phase.payment)
+            .unwrap_or_default();
+        self.phases.push(RatePhase {
+            start,
+            rate,
+            payment,
+            payment_change,
+            interest: Money::ZERO,
+        });
+    }
+
+    pub fn start_allowance_period(
+        &mut self,
+        start: Date,
+        resets: Date,
+        allowance: Money,
+        charge_pct: f64,
+    ) {
+        self.allowance_periods.push(AllowancePeriod {
+            start,
+            resets,
+            allowance,
+            overpaid: Money::ZERO,
+            headroom: allowance,
+            charge_pct,
+            charge: Money::ZERO,
+        });
+    }
+
+    /// Counts an overpayment against the allowance for the current period, charging the early
+    /// repayment charge on whatever has now been overpaid beyond it
+    pub fn record_overpayment(&mut self, amount: Money, rounding: RoundingMode) {
+        if let Some(period) = self.allowance_periods.last_mut() {
+            period.overpaid += amount;
+            period.headroom = period.allowance - period.overpaid;
+            period.charge = (-period.headroom)
+                .max(Money::ZERO)
+                .percentage(period.charge_pct, rounding);
+        }
+    }
+
+    /// The allowance period that `date` falls in
+    pub fn allowance_period_on(&self, date: Date) -> Option<&AllowancePeriod> {
+        self.allowance_periods
+            .iter()
+            .find(|period| period.start <= date && date < period.resets)
+    }
+
+    /// The allowance period containing today, or the nearest end of the schedule when today is
+    /// outside it. If nothing is overpaid in that period the first later one that exceeds its
+    /// allowance is taken instead, so an untouched allowance isn't reported beside a charge.
+    pub fn current_allowance_period(&self) -> Option<&AllowancePeriod> {
+        let first = self.rows.first()?.date;
+        let last = self.rows.last()?.date;
+        let current = self.allowance_period_on(self.today.unwrap_or(first).clamp(first, last))?;
+        if current.overpaid.is_zero()
+            && let Some(later) = self
+                .allowance_periods
+                .iter()
+                .find(|period| period.start > current.start && period.headroom.is_negative())
+        {
+            return Some(later);
+        }
+
+        Some(current)
+    }
+
+    /// Early repayment charges incurred across every period
+    pub fn total_charges(&self) -> Money {
+        self.allowance_periods
+            .iter()
+            .map(|period| period.charge)
+            .sum()
+    }
+
+    /// Adds a closed month to the schedule, attributing its interest to the current phase
+    pub fn push_row(&mut self, row: ScheduleRow) {
+        if let Some(phase) = self.phases.last_mut() {
+            phase.interest += row.interest;
+        }
+        self.rows.push(row);
+    }
+
+    pub fn total_interest(&self) -> Money {
+        self.rows.iter().map(|row| row.interest).sum()
+    }
+
+    pub fn total_paid(&self) -> Money {
+        self.rows
+            .iter()
+            .map(|row| row.payment + row.overpayment)
+            .sum()
+    }
+
+    /// Whether the balance failed to fall over the last `months` months
+    pub fn not_falling_over(&self, months: usize) -> bool {
+        self.rows.len() > months
+            && self.rows[self.rows.len() - 1].closing_balance
+                >= self.rows[self.rows.len() - 1 - months].closing_balance
+    }
+
+    /// Date of the final payment, if the balance was cleared
+    pub fn payoff_date(&self) -> Option<Date> {
+        self.rows
+            .last()
+            .filter(|row| row.closing_balance.is_zero())
+            .map(|row| row.date)
+    }
+
+    pub fn closing_balance(&self) -> Money {
+        self.rows
+            .last()
+            .map(|row| row.closing_balance)
+            .unwrap_or_default()
+    }
+
+    /// Total paid and total interest in today's money, when the schedule has been deflated. An
+    /// empty schedule has nothing to deflate.
+    pub fn real_totals(&self) -> Option<(Money, Money)> {
+        self.rows.first()?.real?;
+        self.rows
+            .iter()
+            .try_fold((Money::ZERO, Money::ZERO), |(paid, interest), row| {
+                row.real
+                    .map(|real| (paid + real.paid, interest + real.interest))
+            })
+    }
+}
+
+const CSV_HEADER: &str = "date,opening_balance,interest,payment,overpayment,closing_balance";
+const REAL_CSV_COLUMNS: &str = ",real_interest,real_paid,real_closing_balance";
+
+fn format_payment_change(change: Money) -> String {
+    if change.is_zero() {
+        String::new()
+    } else if change.is_negative() {
+        format!(" ({:.2})", change)
+    } else {
+        format!(" (+{:.2})", change)
+    }
+}
+
+fn render_table(schedule: &AmortizationSchedule) -> String {
+    let real_totals = schedule.real_totals();
+    let mut output = format!(
+        "{:<12}{:>14}{:>12}{:>12}{:>13}{:>14}",
+        "Date", "Opening", "Interest", "Payment", "Overpayment", "Closing"
+    );
+    if real_totals.is_some() {
+        output.push_str(&format!(
+            "{:>15}{:>12}{:>14}",
+            "Real interest", "Real paid", "Real closing"
+        ));
+    }
+    output.push('\n');
+    for row in &schedule.rows {
+        output.push_str(&format!(
+            "{:<12}{:>14.2}{:>12.2}{:>12.2}{:>13.2}{:>14.2}",
+            row.date.to_string(),
+            row.opening_balance,
+            row.interest,
+            row.payment,
+            row.overpayment,
+            row.closing_balance
+        ));
+        if let Some(real) = row.real {
+            output.push_str(&format!(
+                "{:>15.2}{:>12.2}{:>14.2}",
+                real.interest, real.paid, real.closing_balance
+            ));
+        }
+        output.push('\n');
+    }
+    if schedule.phases.len() > 1 {
+        for phase in &schedule.phases {
+            output.push_str(&format!(
+                "From {} at {:.2}%: monthly payment {:.2}{}, interest {:.2}\n",
+                phase.start,
+                phase.rate,
+                phase.payment,
+                format_payment_change(phase.payment_change),
+                phase.interest
+            ));
+        }
+    }
+    output.push_str(&format!(
+        "Balance after {} months is {:.2}. Total paid is {:.2}, of which {:.2} was interest\n",
+        schedule.rows.len(),
+        schedule.closing_balance(),
+        schedule.total_paid(),
+        schedule.total_interest()
+    ));
+    if let Some((paid, interest)) = real_totals {
+        output.push_str(&format!(
+            "In today's money that is {:.2} paid, of which {:.2} was interest\n",
+            paid, interest
+        ));
+    }
+    if let (Some(payoff_date), Some(last)) = (schedule.payoff_date(), schedule.rows.last()) {
+        output.push_str(&format!(
+            "Paid off on {} after {} payments, the final payment being {:.2}\n",
+            payoff_date,
+            schedule.rows.len(),
+            last.payment + last.overpayment
+        ));
+    }
+    for period in schedule
+        .allowance_periods
+        .iter()
+        .filter(|period| period.charge.is_positive())
+    {
+        output.push_str(&format!(
+            "Early repayment charge of {:.2} in the year from {}, {:.2}% of the {:.2} overpaid above the allowance\n",
+            period.charge,
+            period.start,
+            period.charge_pct,
+            -period.headroom
+        ));
+    }
+    if schedule.overpaying
+        && let Some(period) = schedule.current_allowance_period()
+    {
+        let remaining = if period.headroom.is_negative() {
+            format!("exceeded by {:.2}", -period.headroom)
+        } else {
+            format!("{:.2} remaining", period.headroom)
+        };
+        output.push_str(&format!(
+            "Overpayment allowance from {} is {:.2}, {} before it resets on {}\n",
+            period.start, period.allowance, remaining, period.resets
+        ));
+    }
+
+    output
+}
+
+fn render_csv(schedule: &AmortizationSchedule) -> String {
+    let mut output = CSV_HEADER.to_string();
+    if schedule.real_totals().is_some() {
+        output.push_str(REAL_CSV_COLUMNS);
+    }
+    output.push('\n');
+    for row in &schedule.rows {
+        output.push_str(&format!(
+            "{},{:.2},{:.2},{:.2},{:.2},{:.2}",
+            row.date,
+            row.opening_balance,
+            row.interest,
+            row.payment,
+            row.overpayment,
+            row.closing_balance
+        ));
+        if let Some(real) = row.real {
+            output.push_str(&format!(
+                ",{:.2},{:.2},{:.2}",
+                real.interest, real.paid, real.closing_balance
+            ));
+        }
+        output.push('\n');
+    }
+
+    output
+}
+
+#[derive(Serialize)]
+struct JsonSchedule<'a> {
+    rows: &'a [ScheduleRow],
+    phases: &'a [RatePhase],
+    allowance_periods: &'a [AllowancePeriod],
+    #[serde(serialize_with = "serialize_optional_date")]
+    payoff_date: Option<Date>,
+    total_paid: Money,
+    total_interest: Money,
+    early_repayment_charges: Money,
+    closing_balance: Money,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    real_total_paid: Option<Money>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    real_total_interest: Option<Money>,
+}
+
+fn render_json(schedule: &AmortizationSchedule) -> Result<String> {
+    serde_json::to_string_pretty(&JsonSchedule {
+        rows: &schedule.rows,
+        phases: &schedule.phases,
+        allowance_periods: &schedule.allowance_periods,
+        payoff_date: schedule.payoff_date(),
+        total_paid: schedule.total_paid(),
+        total_interest: schedule.total_interest(),
+        early_repayment_charges: schedule.total_charges(),
+        closing_balance: schedule.closing_balance(),
+        real_total_paid: schedule.real_totals().map(|(paid, _)| paid),
+        real_total_interest: schedule.real_totals().map(|(_, interest)| interest),
+    })
+    .context("Unable to serialise amortization schedule to JSON")
+}
+
+pub fn render_schedule(schedule: &AmortizationSchedule, format: OutputFormat) -> Result<String> {
+    match format {
+        OutputFormat::Table => Ok(render_table(schedule)),
+        OutputFormat::Csv => Ok(render_csv(schedule)),
+        OutputFormat::Json => render_json(schedule),
+    }
+}
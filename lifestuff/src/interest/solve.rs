@@ -0,0 +1,114 @@
+use super::schedule::{self, AmortizationSchedule};
+use super::{LoanParameters, calculate_interest_data_for_period};
+use anyhow::{Result, bail, ensure};
+use lifestuff_types::interest::OutputFormat;
+use lifestuff_types::interest::solve::SolveFor;
+use lifestuff_types::money::{Money, RoundingMode};
+
+/// The smallest amount that clears the balance by the end date, and the schedule it produces
+#[derive(Debug)]
+pub struct Solution {
+    pub amount: Money,
+    pub params: LoanParameters,
+    pub schedule: AmortizationSchedule,
+}
+
+/// Binary searches for the smallest amount, to the penny, in `0..=upper` that clears the balance.
+/// The balance left at the end date never increases as the amount increases, so the first
+/// clearing amount found is the minimum.
+fn smallest_clearing_amount(
+    upper: Money,
+    scenario: impl Fn(Money) -> LoanParameters,
+) -> Option<Money> {
+    let clears = |amount: Money| {
+        calculate_interest_data_for_period(&scenario(amount))
+            .closing_balance()
+            .is_zero()
+    };
+
+    let (mut low, mut high) = (0, upper.to_minor(RoundingMode::HalfUp));
+    if !clears(Money::from_minor(high)) {
+        return None;
+    }
+    while low < high {
+        let mid = low + (high - low) / 2;
+        if clears(Money::from_minor(mid)) {
+            high = mid;
+        } else {
+            low = mid + 1;
+        }
+    }
+
+    Some(Money::from_minor(high))
+}
+
+/// Goal-seeks the amount that clears the balance by the end date, using the same daily
+/// accrual simulation as the schedule so the result matches it exactly
+pub fn solve(params: &LoanParameters, solve_for: SolveFor) -> Result<Solution> {
+    ensure!(
+        !params.open_ended,
+        "An --end-date is needed to solve for the payment that clears the balance by then"
+    );
+
+    let scenario = |amount: Money| match solve_for {
+        SolveFor::Payment => LoanParameters {
+            monthly_payment: amount,
+            ..params.clone()
+        },
+        SolveFor::AnnualOverpayment => LoanParameters {
+            annual_downpayment: Some(amount),
+            ..params.clone()
+        },
+    };
+
+    let Some(amount) = smallest_clearing_amount(params.principal, scenario) else {
+        match solve_for {
+            SolveFor::Payment => bail!(
+                "Unable to clear the balance by {} with a monthly payment",
+                params.end_date
+            ),
+            SolveFor::AnnualOverpayment => bail!(
+                "Unable to clear the balance by {} with annual overpayments, as none fall due before then",
+                params.end_date
+            ),
+        }
+    };
+
+    let params = scenario(amount);
+    let schedule = calculate_interest_data_for_period(&params);
+    Ok(Solution {
+        amount,
+        params,
+        schedule,
+    })
+}
+
+fn describe_solution(solution: &Solution, solve_for: SolveFor) -> String {
+    let amount = match solve_for {
+        SolveFor::Payment => format!("A monthly payment of {:.2}", solution.amount),
+        SolveFor::AnnualOverpayment => format!(
+            "An annual overpayment of {:.2} with a monthly payment of {:.2}",
+            solution.amount, solution.params.monthly_payment
+        ),
+    };
+
+    format!(
+        "{} clears the balance by {}\n",
+        amount, solution.params.end_date
+    )
+}
+
+pub fn render_solution(
+    solution: &Solution,
+    solve_for: SolveFor,
+    format: OutputFormat,
+) -> Result<String> {
+    let rendered = schedule::render_schedule(&solution.schedule, format)?;
+    match format {
+        OutputFormat::Table => Ok(format!(
+            "{rendered}{}",
+            describe_solution(solution, solve_for)
+        )),
+        OutputFormat::Csv | OutputFormat::Json => Ok(rendered),
+    }
+}
@@ -1,17 +1,56 @@
 #[cfg(test)]
 mod interest_tests {
+    use crate::interest::compare::{compare_strategies, render_comparison};
+    use crate::interest::debts::{
+        Debt, DebtParameters, Strategy, parse_debts, plan_debts, render_plans, simulate_plan,
+        target_order,
+    };
+    use crate::interest::from_mortgage::{
+        check_balance, parse_api_date, render_balance_check, resume_date,
+    };
+    use crate::interest::inflation::{
+        Deflator, deflate_savings, deflate_schedule, parse_cpi_series,
+    };
+    use crate::interest::montecarlo::{
+        Percentiles, SplitMix64, percentiles, render_summary, simulate_paths,
+    };
+    use crate::interest::offers::{
+        Offer, annual_percentage_rate, compare_offers, parse_offers, render_offers,
+    };
+    use crate::interest::offset::{parse_offset_schedule, render_offset_summary, summarise_offset};
+    use crate::interest::savings::{SavingsSchedule, project_savings, render_savings, tax_year};
+    use crate::interest::scenario::{merge, parse_scenario, render_scenario};
+    use crate::interest::schedule::{AmortizationSchedule, render_schedule};
+    use crate::interest::solve::{render_solution, solve};
+    use crate::interest::{
+        LoanParameters, anniversary_in_year, annuity_payment, calculate_interest_data_for_period,
+        daily_year_fraction, get_rate_changes, next_anniversary, parse_rate_schedule,
+        payments_remaining, previous_anniversary, schedule_warnings,
+    };
+    use lifestuff_types::interest::compare::Compare;
+    use lifestuff_types::interest::montecarlo::MonteCarlo;
+    use lifestuff_types::interest::savings::{Savings, SavingsCompounding};
+    use lifestuff_types::interest::solve::SolveFor;
+    use lifestuff_types::interest::{
+        Anniversary, Compounding, DayCount, Interest, OutputFormat, RateChange,
+    };
+    use lifestuff_types::money::{Money, RoundingMode};
+    use lifestuff_types::mortgage::MortgageSummary;
+    use std::path::Path;
+    use time::Date;
 
     #[test]
     fn test_handle_interest_calculations_valid() {
         // Test that the interest calculation workflow completes successfully
         // with valid input parameters
-        let interest_args = lifestuff_types::interest::Interest {
-            principal: 100000.0,
-            interest_rate: 5.0,
-            repayment: 2000.0,
+        let interest_args = Interest {
+            principal: Some(Money::from_major(100000)),
+            interest_rate: Some(5.0),
+            repayment: Some(Money::from_major(2000)),
             max_repayment_pct: Some(10),
-            annual_downpayment: Some(5000.0),
-            end_date: "31/12/2025".to_string(),
+            annual_downpayment: Some(Money::from_major(5000)),
+            end_date: Some("31/12/2040".to_string()),
+            ..Interest::default()
         };
 
         // Test the actual calculation logic runs without error
@@ -19,13 +58,13 @@ mod interest_tests {
         assert!(result.is_ok());
 
         // Test that different parameter combinations work correctly
-        let interest_args_no_downpayment = lifestuff_types::interest::Interest {
-            principal: 50000.0,
-            interest_rate: 3.5,
-            repayment: 1500.0,
+        let interest_args_no_downpayment = Interest {
+            principal: Some(Money::from_major(50000)),
+            interest_rate: Some(3.5),
+            repayment: Some(Money::from_major(1500)),
             max_repayment_pct: Some(5),
-            annual_downpayment: None, // Testing different branch of logic
-            end_date: "30/06/2024".to_string(),
+            end_date: Some("30/06/2035".to_string()),
+            ..Interest::default()
         };
 
         let result2 =
@@ -35,28 +74,45 @@ mod interest_tests {
 
     #[test]
     fn test_handle_interest_calculations_invalid_end_date() {
-        let interest_args = lifestuff_types::interest::Interest {
-            principal: 100000.0,
-            interest_rate: 5.0,
-            repayment: 2000.0,
+        let interest_args = Interest {
+            principal: Some(Money::from_major(100000)),
+            interest_rate: Some(5.0),
+            repayment: Some(Money::from_major(2000)),
             max_repayment_pct: Some(10),
-            annual_downpayment: Some(5000.0),
-            end_date: "invalid_date".to_string(),
+            annual_downpayment: Some(Money::from_major(5000)),
+            end_date: Some("invalid_date".to_string()),
+            ..Interest::default()
         };
         let result = crate::interest::handle_interest_calculations(interest_args, false);
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_handle_interest_calculations_end_date_before_start() {
+        let interest_args = Interest {
+            principal: Some(Money::from_major(100000)),
+            interest_rate: Some(5.0),
+            repayment: Some(Money::from_major(1000)),
+            end_date: Some("01/01/2000".to_string()),
+            ..Interest::default()
+        };
+        let error = crate::interest::handle_interest_calculations(interest_args, false)
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("The end date 2000-01-31 is before the simulation starts on"));
+    }
+
     #[test]
     fn test_handle_interest_calculations_zero_principal() {
         // Test that the validation logic properly rejects zero principal
-        let interest_args = lifestuff_types::interest::Interest {
-            principal: 0.0,
-            interest_rate: 5.0,
-            repayment: 2000.0,
+        let interest_args = Interest {
+            principal: Some(Money::from_major(0)),
+            interest_rate: Some(5.0),
+            repayment: Some(Money::from_major(2000)),
             max_repayment_pct: Some(10),
-            annual_downpayment: Some(5000.0),
-            end_date: "31/12/2025".to_string(),
+            annual_downpayment: Some(Money::from_major(5000)),
+            end_date: Some("31/12/2025".to_string()),
+            ..Interest::default()
         };
         let result = crate::interest::handle_interest_calculations(interest_args, false);
         assert!(result.is_err());
@@ -72,13 +128,14 @@ mod interest_tests {
     #[test]
     fn test_handle_interest_calculations_negative_principal() {
         // Test that the validation logic properly rejects negative principal
-        let interest_args = lifestuff_types::interest::Interest {
-            principal: -100000.0,
-            interest_rate: 5.0,
-            repayment: 2000.0,
+        let interest_args = Interest {
+            principal: Some(Money::from_major(-100000)),
+            interest_rate: Some(5.0),
+            repayment: Some(Money::from_major(2000)),
             max_repayment_pct: Some(10),
-            annual_downpayment: Some(5000.0),
-            end_date: "31/12/2025".to_string(),
+            annual_downpayment: Some(Money::from_major(5000)),
+            end_date: Some("31/12/2025".to_string()),
+            ..Interest::default()
         };
         let result = crate::interest::handle_interest_calculations(interest_args, false);
         assert!(result.is_err());
@@ -90,4 +147,1911 @@ mod interest_tests {
                 .contains("positive principal")
         );
     }
+
+    fn date(input: &str) -> Date {
+        crate::dateinfo::DateTimeKeeper::new_from_dmy_str(input, false)
+            .unwrap()
+            .date()
+    }
+
+    fn money(input: &str) -> Money {
+        input.parse().unwrap()
+    }
+
+    fn loan(
+        start_date: &str,
+        end_date: &str,
+        monthly_payment: &str,
+        interest_rate: f64,
+        principal: &str,
+    ) -> LoanParameters {
+        LoanParameters {
+            start_date: date(start_date),
+            end_date: date(end_date),
+            open_ended: false,
+            stop_when_growing: false,
+            term_end: None,
+            principal: money(principal),
+            monthly_payment: money(monthly_payment),
+            interest_rate,
+            rate_changes: vec![],
+            max_annual_repayment_pct: None,
+            annual_downpayment: None,
+            anniversary: Anniversary::default(),
+            early_repayment_charges: vec![],
+            monthly_overpayment: Money::ZERO,
+            offset: vec![],
+            rounding: RoundingMode::HalfEven,
+            compounding: Compounding::Daily,
+            day_count: DayCount::ActualActual,
+        }
+    }
+
+    #[test]
+    fn test_schedule_has_a_row_per_month() {
+        let schedule = calculate_interest_data_for_period(&LoanParameters {
+            annual_downpayment: Some(money("2000")),
+            ..loan("01/01/2025", "31/12/2025", "1000", 0.0, "20000")
+        });
+
+        assert_eq!(schedule.rows.len(), 12);
+        assert_eq!(schedule.rows[0].overpayment, money("2000"));
+        assert_eq!(schedule.rows[1].overpayment, Money::ZERO);
+        assert!(schedule.rows.iter().all(|row| row.payment == money("1000")));
+        assert_eq!(schedule.closing_balance(), money("6000"));
+        assert_eq!(schedule.total_paid(), money("14000"));
+        assert_eq!(schedule.total_interest(), Money::ZERO);
+    }
+
+    #[test]
+    fn test_schedule_rows_chain_balances() {
+        let schedule = calculate_interest_data_for_period(&LoanParameters {
+            max_annual_repayment_pct: Some(10),
+            ..loan("01/02/2025", "31/03/2025", "1000", 3.65, "10000")
+        });
+
+        assert_eq!(schedule.rows.len(), 2);
+        let february = &schedule.rows[0];
+        assert_eq!(february.opening_balance, money("10000"));
+        assert_eq!(february.payment, money("1000"));
+        // 28 days of compounding at 0.01% a day on the post-payment balance, posted to the penny
+        assert_eq!(february.interest, money("25.23"));
+        for row in &schedule.rows {
+            assert_eq!(
+                row.closing_balance,
+                row.opening_balance - row.payment - row.overpayment + row.interest
+            );
+        }
+        assert_eq!(schedule.rows[1].opening_balance, february.closing_balance);
+    }
+
+    #[test]
+    fn test_schedule_large_balance_is_penny_exact() {
+        let schedule = calculate_interest_data_for_period(&LoanParameters {
+            max_annual_repayment_pct: Some(10),
+            ..loan("01/01/2025", "31/12/2049", "1450", 4.19, "250000")
+        });
+
+        assert_eq!(
+            schedule.total_paid(),
+            money("250000") + schedule.total_interest()
+        );
+        assert_eq!(schedule.closing_balance(), Money::ZERO);
+        assert!(
+            schedule.rows.iter().all(|row| row.closing_balance
+                == row.closing_balance.round_to_penny(RoundingMode::HalfEven))
+        );
+    }
+
+    #[test]
+    fn test_schedule_stops_when_paid_off() {
+        let schedule = calculate_interest_data_for_period(&loan(
+            "01/01/2025",
+            "31/12/2026",
+            "1000",
+            0.0,
+            "2500",
+        ));
+
+        assert_eq!(schedule.rows.len(), 3);
+        assert_eq!(schedule.rows[2].payment, money("500"));
+        assert_eq!(schedule.closing_balance(), Money::ZERO);
+    }
+
+    #[test]
+    fn test_rate_change_keeps_payment_without_a_term() {
+        let schedule = calculate_interest_data_for_period(&LoanParameters {
+            rate_changes: vec![(date("01/07/2025"), 12.0)],
+            ..loan("01/01/2025", "31/12/2025", "1000", 0.0, "12000")
+        });
+
+        // The end date only limits the simulation, so it isn't used as the term
+        assert_eq!(schedule.phases.len(), 2);
+        assert_eq!(schedule.phases[1].payment, money("1000"));
+        assert_eq!(schedule.phases[1].payment_change, Money::ZERO);
+        assert!(schedule.rows.iter().all(|row| row.payment == money("1000")));
+        assert!(schedule.closing_balance().is_positive());
+    }
+
+    #[test]
+    fn test_rate_change_recalculates_payment_over_the_term() {
+        let schedule = calculate_interest_data_for_period(&LoanParameters {
+            rate_changes: vec![(date("01/07/2025"), 12.0)],
+            term_end: Some(date("31/12/2025")),
+            ..loan("01/01/2025", "30/09/2025", "1000", 0.0, "12000")
+        });
+
+        assert_eq!(schedule.phases.len(), 2);
+        let (fixed, variable) = (&schedule.phases[0], &schedule.phases[1]);
+        assert_eq!(fixed.payment, money("1000"));
+        assert_eq!(fixed.interest, Money::ZERO);
+        assert_eq!(variable.start, date("01/07/2025"));
+        // 6000 repaid over the 6 months left of the term at 1% a month, even though the
+        // simulation stops after 3 of them
+        assert_eq!(variable.payment, money("1035.29"));
+        assert_eq!(variable.payment_change, money("35.29"));
+        assert!(variable.interest.is_positive());
+        assert_eq!(
+            fixed.interest + variable.interest,
+            schedule.total_interest()
+        );
+        assert_eq!(schedule.rows.len(), 9);
+        assert_eq!(schedule.rows[5].payment, money("1000"));
+        assert_eq!(schedule.rows[6].payment, money("1035.29"));
+        assert!(schedule.closing_balance().is_positive());
+    }
+
+    #[test]
+    fn test_annuity_payment() {
+        assert_eq!(
+            annuity_payment(money("6000"), 12.0, 6, RoundingMode::HalfEven),
+            money("1035.29")
+        );
+        assert_eq!(
+            annuity_payment(money("6000"), 0.0, 6, RoundingMode::HalfEven),
+            money("1000")
+        );
+        assert_eq!(
+            payments_remaining(&date("01/07/2025"), &date("31/12/2026")),
+            18
+        );
+    }
+
+    #[test]
+    fn test_parse_rate_change() {
+        let change = "01/04/2027=7.49".parse::<RateChange>().unwrap();
+        assert_eq!(change.date, "01/04/2027");
+        assert_eq!(change.rate, 7.49);
+        assert_eq!("01/04/2027,7.49%".parse::<RateChange>(), Ok(change));
+        assert!("7.49".parse::<RateChange>().is_err());
+        assert!("01/04/2027=-1".parse::<RateChange>().is_err());
+        assert!("01/04/2027=svr".parse::<RateChange>().is_err());
+    }
+
+    #[test]
+    fn test_parse_rate_schedule() {
+        let contents = "date,rate\n\
+                        # end of the fixed period\n\
+                        01/04/2027,7.49\n\
+                        \n\
+                        01/04/2028=6.99\n";
+        let changes = parse_rate_schedule(contents).unwrap();
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[1].rate, 6.99);
+
+        assert!(parse_rate_schedule("01/04/2027\n").is_err());
+        // A row starting with a letter past the header is a typo rather than skipped
+        let error =
+            parse_rate_schedule("date,rate\n01/04/2027,7.49\nApril 2028 6.99\n").unwrap_err();
+        assert!(error.to_string().starts_with("Line 3: invalid rate change"));
+    }
+
+    fn rate_change_args(changes: &[&str]) -> Interest {
+        Interest {
+            principal: Some(Money::from_major(100000)),
+            interest_rate: Some(4.19),
+            rate_changes: changes
+                .iter()
+                .map(|change| change.parse().unwrap())
+                .collect(),
+            repayment: Some(Money::from_major(1000)),
+            max_repayment_pct: Some(10),
+            end_date: Some("31/12/2030".to_string()),
+            ..Interest::default()
+        }
+    }
+
+    #[test]
+    fn test_get_rate_changes_aligns_to_payment_dates() {
+        let args = rate_change_args(&["15/03/2026=6", "01/02/2026=5"]);
+        let changes = get_rate_changes(&args, &date("01/01/2026"), false).unwrap();
+        assert_eq!(
+            changes,
+            vec![(date("01/02/2026"), 5.0), (date("01/04/2026"), 6.0)]
+        );
+    }
+
+    #[test]
+    fn test_get_rate_changes_invalid() {
+        let duplicate = rate_change_args(&["15/03/2026=6", "01/04/2026=5"]);
+        assert!(get_rate_changes(&duplicate, &date("01/01/2026"), false).is_err());
+
+        let before_start = rate_change_args(&["01/01/2026=6"]);
+        assert!(get_rate_changes(&before_start, &date("01/01/2026"), false).is_err());
+    }
+
+    #[test]
+    fn test_render_schedule_formats() {
+        let schedule = calculate_interest_data_for_period(&LoanParameters {
+            max_annual_repayment_pct: Some(10),
+            rate_changes: vec![(date("01/03/2025"), 6.0)],
+            ..loan("01/01/2025", "31/03/2025", "1000", 5.0, "10000")
+        });
+
+        let csv = render_schedule(&schedule, OutputFormat::Csv).unwrap();
+        let lines = csv.lines().collect::<Vec<&str>>();
+        assert_eq!(lines.len(), 4);
+        assert_eq!(
+            lines[0],
+            "date,opening_balance,interest,payment,overpayment,closing_balance"
+        );
+        assert!(lines[1].starts_with("2025-01-01,10000.00,"));
+
+        let json = render_schedule(&schedule, OutputFormat::Json).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed["rows"].as_array().unwrap().len(), 3);
+        assert_eq!(parsed["rows"][0]["date"], "2025-01-01");
+        assert_eq!(parsed["rows"][0]["overpayment"], 1000.0);
+        assert_eq!(parsed["phases"].as_array().unwrap().len(), 2);
+        assert_eq!(parsed["phases"][1]["rate"], 6.0);
+
+        let table = render_schedule(&schedule, OutputFormat::Table).unwrap();
+        assert!(table.contains("Balance after 3 months"));
+        assert!(table.contains("From 2025-03-01 at 6.00%"));
+    }
+
+    #[test]
+    fn test_monthly_overpayment() {
+        let schedule = calculate_interest_data_for_period(&LoanParameters {
+            monthly_overpayment: money("250"),
+            ..loan("01/01/2025", "31/12/2025", "1000", 0.0, "4050")
+        });
+
+        assert_eq!(schedule.rows.len(), 4);
+        assert_eq!(schedule.rows[0].overpayment, money("250"));
+        assert_eq!(schedule.rows[3].payment, money("300"));
+        assert_eq!(schedule.rows[3].overpayment, Money::ZERO);
+        assert_eq!(schedule.payoff_date(), Some(date("01/04/2025")));
+    }
+
+    #[test]
+    fn test_compare_strategies() {
+        let params = LoanParameters {
+            max_annual_repayment_pct: Some(10),
+            ..loan("01/01/2025", "31/12/2034", "700", 5.0, "60000")
+        };
+        let compare_args = Compare {
+            monthly_overpayment: vec![money("200")],
+            max_repayment_pct: None,
+            annual_downpayment: Some(money("3000")),
+        };
+
+        let summaries = compare_strategies(&params, &compare_args).unwrap();
+        let names = summaries
+            .iter()
+            .map(|summary| summary.name.as_str())
+            .collect::<Vec<&str>>();
+        assert_eq!(
+            names,
+            vec![
+                "No overpayment",
+                "10% annual cap",
+                "3000.00 a year",
+                "200.00 a month"
+            ]
+        );
+
+        let baseline = &summaries[0];
+        assert_eq!(baseline.interest_saved, Money::ZERO);
+        for summary in &summaries[1..] {
+            assert!(summary.interest_saved.is_positive());
+            assert_eq!(
+                summary.total_interest + summary.interest_saved,
+                baseline.total_interest
+            );
+            assert!(summary.payoff_date.unwrap() < baseline.payoff_date.unwrap());
+        }
+    }
+
+    #[test]
+    fn test_compare_without_strategies_fails() {
+        let compare_args = Compare {
+            monthly_overpayment: vec![],
+            max_repayment_pct: None,
+            annual_downpayment: None,
+        };
+        let params = loan("01/01/2025", "31/12/2034", "600", 5.0, "60000");
+        assert!(compare_strategies(&params, &compare_args).is_err());
+    }
+
+    #[test]
+    fn test_render_comparison_formats() {
+        let compare_args = Compare {
+            monthly_overpayment: vec![money("100")],
+            max_repayment_pct: None,
+            annual_downpayment: None,
+        };
+        // Not paid off by the end date without overpaying
+        let params = loan("01/01/2025", "31/12/2025", "1000", 0.0, "12500");
+        let summaries = compare_strategies(&params, &compare_args).unwrap();
+        assert_eq!(summaries[0].payoff_date, None);
+        assert_eq!(summaries[0].closing_balance, money("500"));
+        assert_eq!(summaries[1].payoff_date, Some(date("01/12/2025")));
+
+        let csv = render_comparison(&summaries, OutputFormat::Csv).unwrap();
+        let lines = csv.lines().collect::<Vec<&str>>();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[1], "No overpayment,,12,0.00,0.00,0.00,0.00,500.00");
+
+        let json = render_comparison(&summaries, OutputFormat::Json).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert!(parsed[0]["payoff_date"].is_null());
+        assert_eq!(parsed[1]["payoff_date"], "2025-12-01");
+
+        let table = render_comparison(&summaries, OutputFormat::Table).unwrap();
+        assert!(table.contains("still have a balance"));
+    }
+
+    #[test]
+    fn test_solve_payment_without_interest() {
+        let params = loan("01/01/2025", "31/12/2025", "0", 0.0, "12000");
+        let solution = solve(&params, SolveFor::Payment).unwrap();
+        assert_eq!(solution.amount, money("1000"));
+        assert_eq!(solution.schedule.rows.len(), 12);
+        assert_eq!(solution.schedule.payoff_date(), Some(date("01/12/2025")));
+    }
+
+    #[test]
+    fn test_solve_payment_matches_simulator() {
+        let params = LoanParameters {
+            max_annual_repayment_pct: Some(10),
+            ..loan("01/01/2025", "31/12/2034", "0", 4.19, "150000")
+        };
+        let solution = solve(&params, SolveFor::Payment).unwrap();
+        assert_eq!(solution.schedule.closing_balance(), Money::ZERO);
+
+        // A penny less leaves a balance at the end date
+        let short = calculate_interest_data_for_period(&LoanParameters {
+            monthly_payment: solution.amount - Money::from_minor(1),
+            ..params
+        });
+        assert!(short.closing_balance().is_positive());
+    }
+
+    #[test]
+    fn test_solve_annual_overpayment() {
+        let params = loan("01/01/2025", "31/12/2025", "500", 0.0, "12000");
+        let solution = solve(&params, SolveFor::AnnualOverpayment).unwrap();
+        assert_eq!(solution.amount, money("6000"));
+        assert_eq!(solution.schedule.rows[0].overpayment, money("6000"));
+
+        let table =
+            render_solution(&solution, SolveFor::AnnualOverpayment, OutputFormat::Table).unwrap();
+        assert!(
+            table.contains("An annual overpayment of 6000.00 with a monthly payment of 500.00")
+        );
+    }
+
+    #[test]
+    fn test_solve_annual_overpayment_without_january_fails() {
+        let params = loan("01/02/2025", "30/11/2025", "100", 0.0, "12000");
+        assert!(solve(&params, SolveFor::AnnualOverpayment).is_err());
+    }
+
+    #[test]
+    fn test_open_ended_projection_to_payoff() {
+        let params = LoanParameters {
+            open_ended: true,
+            rate_changes: vec![(date("01/01/2027"), 6.0)],
+            ..loan("01/01/2025", "31/12/2124", "1000", 4.0, "50000")
+        };
+        let schedule = calculate_interest_data_for_period(&params);
+
+        let payoff_date = schedule.payoff_date().unwrap();
+        assert!(payoff_date > date("01/01/2029") && payoff_date < date("01/01/2031"));
+        // The payment is kept when the rate changes, moving the payoff date instead
+        assert_eq!(schedule.phases[1].payment, money("1000"));
+        assert!(schedule.rows.last().unwrap().payment < money("1000"));
+        assert!(schedule_warnings(&schedule, &params).is_empty());
+
+        let table = render_schedule(&schedule, OutputFormat::Table).unwrap();
+        assert!(table.contains(&format!(
+            "Paid off on {payoff_date} after {} payments",
+            schedule.rows.len()
+        )));
+        let json = render_schedule(&schedule, OutputFormat::Json).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed["payoff_date"], payoff_date.to_string());
+    }
+
+    #[test]
+    fn test_warns_when_payment_does_not_cover_interest() {
+        let params = LoanParameters {
+            open_ended: true,
+            ..loan("01/01/2025", "31/12/2034", "100", 12.0, "100000")
+        };
+        let schedule = calculate_interest_data_for_period(&params);
+
+        let warnings = schedule_warnings(&schedule, &params);
+        assert_eq!(warnings.len(), 2);
+        assert!(warnings[0].contains("doesn't cover the interest"));
+        // The balance never falls, so the projection stops rather than running to the cap
+        assert!(schedule.diverging);
+        assert_eq!(schedule.rows.len(), 2);
+        assert!(warnings[1].contains(
+            "the balance is not falling, so it is never cleared. The projection stops on 2025-02-01"
+        ));
+        assert!(schedule.closing_balance() > money("100000"));
+    }
+
+    #[test]
+    fn test_growing_balance_runs_until_nothing_can_turn_it_around() {
+        // A rate cut to come can still clear the balance
+        let cut = LoanParameters {
+            open_ended: true,
+            rate_changes: vec![(date("01/01/2026"), 0.0)],
+            ..loan("01/01/2025", "31/12/2124", "1000", 24.0, "100000")
+        };
+        let schedule = calculate_interest_data_for_period(&cut);
+        assert!(!schedule.diverging);
+        assert!(schedule.payoff_date().is_some());
+
+        // So can an annual overpayment, even though the balance grows between them
+        let overpaying = LoanParameters {
+            open_ended: true,
+            annual_downpayment: Some(money("20000")),
+            ..loan("01/01/2025", "31/12/2124", "100", 12.0, "100000")
+        };
+        let schedule = calculate_interest_data_for_period(&overpaying);
+        assert!(!schedule.diverging);
+        assert!(schedule.payoff_date().is_some());
+
+        // A fixed end date is simulated in full
+        let bounded = loan("01/01/2025", "31/12/2026", "100", 12.0, "100000");
+        let schedule = calculate_interest_data_for_period(&bounded);
+        assert!(!schedule.diverging);
+        assert_eq!(schedule.rows.len(), 24);
+    }
+
+    #[test]
+    fn test_solve_needs_end_date() {
+        let params = LoanParameters {
+            open_ended: true,
+            ..loan("01/01/2025", "31/12/2124", "0", 4.0, "50000")
+        };
+        assert!(solve(&params, SolveFor::Payment).is_err());
+    }
+
+    #[test]
+    fn test_handle_interest_calculations_without_end_date() {
+        let mut interest_args = rate_change_args(&[]);
+        interest_args.end_date = None;
+        interest_args.repayment = Some(Money::from_major(2000));
+        assert!(crate::interest::handle_interest_calculations(interest_args, false).is_ok());
+    }
+
+    fn monthly_interest(
+        compounding: Compounding,
+        day_count: DayCount,
+        payment: &str,
+    ) -> Vec<Money> {
+        calculate_interest_data_for_period(&LoanParameters {
+            compounding,
+            day_count,
+            ..loan("01/01/2025", "31/03/2025", payment, 12.0, "12000")
+        })
+        .rows
+        .iter()
+        .map(|row| row.interest)
+        .collect()
+    }
+
+    #[test]
+    fn test_monthly_rest_thirty_360() {
+        // 1% of the balance each month, compounding monthly
+        assert_eq!(
+            monthly_interest(Compounding::Monthly, DayCount::Thirty360, "0"),
+            vec![money("120"), money("121.20"), money("122.41")]
+        );
+        // Interest is on the balance after the payment
+        assert_eq!(
+            monthly_interest(Compounding::Monthly, DayCount::Thirty360, "1000"),
+            vec![money("110"), money("101.10"), money("92.11")]
+        );
+    }
+
+    #[test]
+    fn test_annual_rest_ignores_payments_until_the_year_end() {
+        assert_eq!(
+            monthly_interest(Compounding::Annual, DayCount::Thirty360, "1000"),
+            vec![money("110"), money("110"), money("110")]
+        );
+    }
+
+    #[test]
+    fn test_day_counts() {
+        let interest = |day_count| monthly_interest(Compounding::Monthly, day_count, "0")[0];
+        // 31 days of January on 12000 at 12%
+        assert_eq!(interest(DayCount::Actual360), money("124"));
+        assert_eq!(interest(DayCount::Actual365), money("122.30"));
+        assert_eq!(interest(DayCount::ActualActual), money("122.30"));
+
+        assert_eq!(
+            daily_year_fraction(&date("01/02/2024"), DayCount::ActualActual),
+            1.0 / 366.0
+        );
+        assert_eq!(
+            daily_year_fraction(&date("01/02/2024"), DayCount::Actual365),
+            1.0 / 365.0
+        );
+    }
+
+    #[test]
+    fn test_daily_compounding_exceeds_monthly() {
+        let daily = monthly_interest(Compounding::Daily, DayCount::Actual365, "0");
+        let monthly = monthly_interest(Compounding::Monthly, DayCount::Actual365, "0");
+        assert!(daily[0] > monthly[0]);
+    }
+
+    fn offset_loan(offset: Vec<(Date, Money)>) -> LoanParameters {
+        LoanParameters {
+            offset,
+            compounding: Compounding::Monthly,
+            day_count: DayCount::Thirty360,
+            ..loan("01/01/2025", "31/12/2025", "0", 12.0, "100000")
+        }
+    }
+
+    #[test]
+    fn test_offset_reduces_the_balance_charged() {
+        let schedule = calculate_interest_data_for_period(&offset_loan(vec![(
+            date("01/01/2025"),
+            money("50000"),
+        )]));
+        assert_eq!(schedule.rows[0].interest, money("500"));
+        // Interest is charged on the principal, which the offset doesn't reduce
+        assert_eq!(schedule.rows[1].opening_balance, money("100500"));
+        assert_eq!(schedule.rows[1].interest, money("505"));
+
+        let fully_offset = calculate_interest_data_for_period(&offset_loan(vec![(
+            date("01/01/2025"),
+            money("150000"),
+        )]));
+        assert_eq!(fully_offset.total_interest(), Money::ZERO);
+    }
+
+    #[test]
+    fn test_dated_offset_balances() {
+        let schedule = calculate_interest_data_for_period(&offset_loan(vec![
+            (date("01/02/2025"), money("50000")),
+            (date("01/03/2025"), money("100000")),
+        ]));
+        assert_eq!(schedule.rows[0].interest, money("1000"));
+        assert_eq!(schedule.rows[1].interest, money("510"));
+        assert_eq!(schedule.rows[2].interest, money("15.10"));
+    }
+
+    #[test]
+    fn test_summarise_offset() {
+        let params = LoanParameters {
+            principal: money("12000"),
+            ..offset_loan(vec![(date("01/01/2025"), money("6000"))])
+        };
+        let schedule = calculate_interest_data_for_period(&params);
+        let summary = summarise_offset(&params, &schedule, 6.0);
+
+        // A year of simple interest on 6000 at 6%
+        assert_eq!(summary.savings_interest, money("360"));
+        // 12% compounding monthly on the 6000 that isn't offset
+        assert_eq!(schedule.total_interest(), money("760.96"));
+        assert_eq!(summary.interest_saved, money("760.93"));
+        assert_eq!(summary.net_benefit(), money("400.93"));
+        assert!(summary.effective_rate > 6.0 && summary.effective_rate < 6.5);
+
+        let rendered = render_offset_summary(&summary);
+        assert!(rendered.contains("saves 760.93 interest"));
+        assert!(rendered.contains("offsetting is 400.93 better off"));
+    }
+
+    #[test]
+    fn test_parse_offset_schedule() {
+        let contents = "date,balance\n\
+                        # savings pot\n\
+                        01/01/2025,10000\n\
+                        01/06/2025,£12,500.50\n";
+        // Only the first comma separates the date, so thousands separators are kept
+        let balances = parse_offset_schedule(contents, false).unwrap();
+        assert_eq!(
+            balances,
+            vec![
+                (date("01/01/2025"), money("10000")),
+                (date("01/06/2025"), money("12500.50"))
+            ]
+        );
+
+        assert!(parse_offset_schedule("01/01/2025\n", false).is_err());
+        assert!(parse_offset_schedule("01/01/2025,-100\n", false).is_err());
+    }
+
+    fn savings_args(compounding: SavingsCompounding) -> Savings {
+        Savings {
+            contribution: Money::ZERO,
+            compounding,
+            annual_cap: None,
+            isa: false,
+            tax_rate: None,
+            allowance: money("1000"),
+        }
+    }
+
+    fn project(params: LoanParameters, savings_args: &Savings) -> SavingsSchedule {
+        project_savings(
+            &LoanParameters {
+                day_count: DayCount::Thirty360,
+                ..params
+            },
+            savings_args,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_tax_year() {
+        assert_eq!(tax_year(&date("05/04/2025")), 2024);
+        assert_eq!(tax_year(&date("06/04/2025")), 2025);
+        assert_eq!(tax_year(&date("31/12/2025")), 2025);
+    }
+
+    #[test]
+    fn test_savings_compounding_frequency() {
+        let params = loan("01/01/2025", "31/12/2025", "0", 12.0, "10000");
+
+        let monthly = project(params.clone(), &savings_args(SavingsCompounding::Monthly));
+        assert_eq!(monthly.rows.len(), 12);
+        assert_eq!(monthly.rows[0].interest, money("100"));
+        assert_eq!(monthly.rows[1].interest, money("101"));
+        // 1% a month compounded for a year
+        assert_eq!(monthly.closing_balance(), money("11268.25"));
+
+        let quarterly = project(params.clone(), &savings_args(SavingsCompounding::Quarterly));
+        let credited = quarterly
+            .rows
+            .iter()
+            .map(|row| row.interest.is_positive())
+            .collect::<Vec<bool>>();
+        assert_eq!(credited, [false, false, true].repeat(4));
+        assert_eq!(quarterly.rows[2].interest, money("300"));
+
+        let annually = project(params, &savings_args(SavingsCompounding::Annually));
+        assert_eq!(annually.total_interest(), money("1200"));
+        assert!(monthly.total_interest() > quarterly.total_interest());
+        assert!(quarterly.total_interest() > annually.total_interest());
+    }
+
+    #[test]
+    fn test_savings_isa_caps_contributions_per_tax_year() {
+        let args = Savings {
+            contribution: money("3000"),
+            isa: true,
+            ..savings_args(SavingsCompounding::Monthly)
+        };
+        let schedule = project(loan("01/01/2025", "31/12/2025", "0", 0.0, "0"), &args);
+
+        // Contributions up to 1 April fall in the 2024 tax year, from 1 May in the 2025 tax year
+        let contributions = schedule
+            .rows
+            .iter()
+            .map(|row| row.contribution)
+            .collect::<Vec<Money>>();
+        assert_eq!(contributions[..10], [money("3000"); 10]);
+        assert_eq!(contributions[10], money("2000"));
+        assert_eq!(contributions[11], Money::ZERO);
+        assert_eq!(schedule.total_contributions(), money("32000"));
+    }
+
+    #[test]
+    fn test_savings_tax_above_allowance() {
+        let args = Savings {
+            tax_rate: Some(20.0),
+            allowance: money("250"),
+            ..savings_args(SavingsCompounding::Monthly)
+        };
+        let schedule = project(loan("01/05/2025", "31/07/2025", "0", 12.0, "10000"), &args);
+
+        assert_eq!(schedule.rows[0].interest, money("100"));
+        assert_eq!(schedule.rows[0].tax, Money::ZERO);
+        assert_eq!(schedule.rows[1].interest, money("101"));
+        assert_eq!(schedule.rows[1].tax, Money::ZERO);
+        // 102.01 of interest, of which 53.01 is over the allowance
+        assert_eq!(schedule.rows[2].interest, money("102.01"));
+        assert_eq!(schedule.rows[2].tax, money("10.60"));
+        assert_eq!(
+            schedule.closing_balance(),
+            money("10000") + schedule.total_interest() - schedule.total_tax()
+        );
+
+        let csv = render_savings(&schedule, OutputFormat::Csv).unwrap();
+        assert_eq!(
+            csv.lines().next().unwrap(),
+            "date,opening_balance,contribution,interest,tax,closing_balance"
+        );
+        let table = render_savings(&schedule, OutputFormat::Table).unwrap();
+        assert!(table.contains("less 10.60 tax"));
+    }
+
+    #[test]
+    fn test_savings_needs_end_date() {
+        let params = LoanParameters {
+            open_ended: true,
+            ..loan("01/01/2025", "31/12/2124", "0", 4.0, "50000")
+        };
+        assert!(project_savings(&params, &savings_args(SavingsCompounding::Monthly)).is_err());
+    }
+
+    #[test]
+    fn test_anniversary_parse() {
+        assert_eq!(
+            "15/06".parse::<Anniversary>(),
+            Ok(Anniversary { day: 15, month: 6 })
+        );
+        assert_eq!(
+            "29/02".parse::<Anniversary>(),
+            Ok(Anniversary { day: 29, month: 2 })
+        );
+        assert!("31/04".parse::<Anniversary>().is_err());
+        assert!("01/13".parse::<Anniversary>().is_err());
+        assert!("0/1".parse::<Anniversary>().is_err());
+        assert!("15-06".parse::<Anniversary>().is_err());
+    }
+
+    #[test]
+    fn test_anniversary_dates() {
+        let leap_day = Anniversary { day: 29, month: 2 };
+        assert_eq!(anniversary_in_year(leap_day, 2025), date("28/02/2025"));
+        assert_eq!(anniversary_in_year(leap_day, 2028), date("29/02/2028"));
+
+        let midsummer = Anniversary { day: 15, month: 6 };
+        assert_eq!(
+            previous_anniversary(&date("01/01/2025"), midsummer),
+            date("15/06/2024")
+        );
+        assert_eq!(
+            previous_anniversary(&date("15/06/2025"), midsummer),
+            date("15/06/2025")
+        );
+        assert_eq!(
+            next_anniversary(&date("15/06/2025"), midsummer),
+            date("15/06/2026")
+        );
+    }
+
+    #[test]
+    fn test_anniversary_resets_allowance() {
+        let schedule = calculate_interest_data_for_period(&LoanParameters {
+            max_annual_repayment_pct: Some(10),
+            anniversary: Anniversary { day: 15, month: 6 },
+            ..loan("01/01/2025", "31/12/2025", "1000", 0.0, "20000")
+        });
+
+        // Nothing is overpaid in January, then 10% of the 14000 owed on the anniversary is
+        // overpaid with the July payment
+        assert!(schedule.rows[0].overpayment.is_zero());
+        assert_eq!(schedule.rows[6].date, date("01/07/2025"));
+        assert_eq!(schedule.rows[6].overpayment, money("1400"));
+
+        let periods = &schedule.allowance_periods;
+        assert_eq!(periods.len(), 2);
+        assert_eq!(periods[0].start, date("15/06/2024"));
+        assert_eq!(periods[0].resets, date("15/06/2025"));
+        assert_eq!(periods[0].headroom, money("2000"));
+        assert_eq!(periods[1].start, date("15/06/2025"));
+        assert_eq!(periods[1].allowance, money("1400"));
+        assert!(periods[1].headroom.is_zero());
+
+        let output = render_schedule(&schedule, OutputFormat::Table).unwrap();
+        assert!(output.contains(
+            "Overpayment allowance from 2024-06-15 is 2000.00, 2000.00 remaining before it resets on 2025-06-15"
+        ));
+    }
+
+    #[test]
+    fn test_allowance_reported_only_with_an_overpayment() {
+        let params = LoanParameters {
+            anniversary: Anniversary { day: 15, month: 6 },
+            ..loan("01/09/2025", "31/12/2026", "1000", 0.0, "40000")
+        };
+        let output = render_schedule(
+            &calculate_interest_data_for_period(&params),
+            OutputFormat::Table,
+        )
+        .unwrap();
+        assert!(!output.contains("Overpayment allowance"));
+
+        // The allowance for today's year is reported, with today kept within the schedule
+        let mut schedule = calculate_interest_data_for_period(&LoanParameters {
+            annual_downpayment: Some(money("500")),
+            end_date: date("31/12/2027"),
+            ..params
+        });
+        let allowance_from = |schedule: &AmortizationSchedule| {
+            schedule
+                .current_allowance_period()
+                .map(|period| period.start)
+        };
+        schedule.today = Some(date("01/08/2027"));
+        assert_eq!(allowance_from(&schedule), Some(date("15/06/2027")));
+        schedule.today = Some(date("01/01/2035"));
+        assert_eq!(allowance_from(&schedule), Some(date("15/06/2027")));
+        schedule.today = Some(date("01/01/2026"));
+        assert_eq!(allowance_from(&schedule), Some(date("15/06/2025")));
+        let output = render_schedule(&schedule, OutputFormat::Table).unwrap();
+        assert!(output.contains(
+            "Overpayment allowance from 2025-06-15 is 4000.00, 4000.00 remaining before it resets on 2026-06-15"
+        ));
+    }
+
+    #[test]
+    fn test_allowance_exceeded_warns() {
+        let params = LoanParameters {
+            annual_downpayment: Some(money("3000")),
+            anniversary: Anniversary { day: 15, month: 6 },
+            ..loan("01/01/2025", "31/12/2025", "1000", 0.0, "20000")
+        };
+        let schedule = calculate_interest_data_for_period(&params);
+
+        assert_eq!(schedule.allowance_periods[1].overpaid, money("3000"));
+        assert_eq!(schedule.allowance_periods[1].headroom, money("-1600"));
+        let warnings = schedule_warnings(&schedule, &params);
+        assert!(
+            warnings
+                .iter()
+                .any(|warning| warning.contains("exceed the allowance of 1400.00"))
+        );
+        let output = render_schedule(&schedule, OutputFormat::Json).unwrap();
+        assert!(output.contains("\"allowance_periods\""));
+    }
+
+    #[test]
+    fn test_handle_interest_calculations_requires_principal() {
+        let interest_args = Interest {
+            principal: None,
+            repayment: Some(Money::from_major(1000)),
+            ..rate_change_args(&[])
+        };
+        let err = crate::interest::handle_interest_calculations(interest_args, false).unwrap_err();
+        assert!(err.to_string().contains("--principal is required"));
+    }
+
+    fn mortgage_summary(last_payment_date: &str, current_balance: &str) -> MortgageSummary {
+        MortgageSummary {
+            id: Some(1),
+            initial_principal: money("12000"),
+            interest_rate: 0.0,
+            term_years: 1,
+            monthly_payment: money("1000"),
+            start_date: "2025-01-01".to_string(),
+            end_date: None,
+            status: "active".to_string(),
+            previous_mortgage_id: None,
+            notes: None,
+            created_at: None,
+            payments_made: 6,
+            total_paid: money("6000"),
+            extra_payments: Money::ZERO,
+            current_balance: money(current_balance),
+            last_payment_date: last_payment_date.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_parse_api_date() {
+        assert_eq!(
+            parse_api_date("2025-03-15", false).unwrap(),
+            date("15/03/2025")
+        );
+        assert_eq!(
+            parse_api_date("2025-03-15T09:30:00Z", false).unwrap(),
+            date("15/03/2025")
+        );
+        assert_eq!(
+            parse_api_date("15/03/2025", false).unwrap(),
+            date("15/03/2025")
+        );
+        assert!(parse_api_date("yesterday", false).is_err());
+    }
+
+    #[test]
+    fn test_resume_date() {
+        let summary = mortgage_summary("2025-06-01", "6000");
+        assert_eq!(
+            resume_date(&summary, false).unwrap().date(),
+            date("01/07/2025")
+        );
+
+        // Nothing paid yet, so the simulation starts at completion
+        let summary = mortgage_summary("", "12000");
+        assert_eq!(
+            resume_date(&summary, false).unwrap().date(),
+            date("01/01/2025")
+        );
+    }
+
+    #[test]
+    fn test_check_balance() {
+        let params = loan("01/07/2025", "31/12/2025", "1000", 0.0, "5000");
+
+        // Six payments of 1000 would leave 6000, but 5000 is owed after overpaying
+        let check = check_balance(&mortgage_summary("2025-06-01", "5000"), &params, false)
+            .unwrap()
+            .unwrap();
+        assert_eq!(check.date, date("01/06/2025"));
+        assert_eq!(check.projected, money("6000"));
+        assert_eq!(check.ahead_by(), money("1000"));
+        assert!(render_balance_check(&check).contains("1000.00 ahead of schedule"));
+
+        let check = check_balance(&mortgage_summary("2025-06-01", "6500"), &params, false)
+            .unwrap()
+            .unwrap();
+        assert!(render_balance_check(&check).contains("500.00 behind schedule"));
+
+        assert!(
+            check_balance(&mortgage_summary("", "12000"), &params, false)
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_early_repayment_charges() {
+        let params = LoanParameters {
+            annual_downpayment: Some(money("3000")),
+            anniversary: Anniversary { day: 15, month: 6 },
+            early_repayment_charges: vec![5.0, 4.0],
+            ..loan("01/01/2025", "31/12/2026", "1000", 0.0, "24000")
+        };
+        let schedule = calculate_interest_data_for_period(&params);
+
+        // 10% of the 18000 owed on the first anniversary is allowed, so 4% is charged on the
+        // 1200 above it. The ERC has ended by the second anniversary.
+        let periods = &schedule.allowance_periods;
+        assert_eq!(periods.len(), 3);
+        assert_eq!(periods[0].charge_pct, 5.0);
+        assert!(periods[0].charge.is_zero());
+        assert_eq!(periods[1].allowance, money("1800"));
+        assert_eq!(periods[1].charge, money("48"));
+        assert_eq!(periods[2].charge_pct, 0.0);
+        assert!(periods[2].charge.is_zero());
+        assert_eq!(schedule.total_charges(), money("48"));
+
+        // The charge replaces the warning about exceeding the allowance
+        assert!(schedule_warnings(&schedule, &params).is_empty());
+        let output = render_schedule(&schedule, OutputFormat::Table).unwrap();
+        assert!(output.contains(
+            "Early repayment charge of 48.00 in the year from 2025-06-15, 4.00% of the 1200.00 overpaid above the allowance"
+        ));
+        // The untouched first year gives way to the one charged
+        assert!(output.contains(
+            "Overpayment allowance from 2025-06-15 is 1800.00, exceeded by 1200.00 before it resets on 2026-06-15"
+        ));
+    }
+
+    #[test]
+    fn test_early_repayment_charges_above_a_percentage_allowance() {
+        let params = LoanParameters {
+            max_annual_repayment_pct: Some(5),
+            annual_downpayment: Some(money("3000")),
+            anniversary: Anniversary { day: 15, month: 6 },
+            early_repayment_charges: vec![5.0, 4.0],
+            ..loan("01/01/2025", "31/12/2026", "1000", 0.0, "24000")
+        };
+        let schedule = calculate_interest_data_for_period(&params);
+
+        // The fixed 3000 is overpaid, but only 5% of the 18000 owed on the first anniversary is
+        // allowed, so 4% is charged on the 2100 above it
+        let periods = &schedule.allowance_periods;
+        assert_eq!(periods[1].allowance, money("900"));
+        assert_eq!(periods[1].overpaid, money("3000"));
+        assert_eq!(periods[1].charge, money("84"));
+        assert_eq!(schedule.total_charges(), money("84"));
+
+        // Comparing strategies keeps the 5% allowance for the fixed overpayment
+        let compare_args = Compare {
+            monthly_overpayment: vec![],
+            max_repayment_pct: None,
+            annual_downpayment: None,
+        };
+        let summaries = compare_strategies(&params, &compare_args).unwrap();
+        assert_eq!(summaries.len(), 3);
+        assert!(summaries[0].early_repayment_charges.is_zero());
+        assert!(summaries[1].early_repayment_charges.is_zero());
+        assert_eq!(summaries[2].early_repayment_charges, money("84"));
+    }
+
+    #[test]
+    fn test_compare_net_of_early_repayment_charges() {
+        let compare_args = Compare {
+            monthly_overpayment: vec![],
+            max_repayment_pct: Some(10),
+            annual_downpayment: Some(money("20000")),
+        };
+        let params = LoanParameters {
+            early_repayment_charges: vec![5.0, 5.0, 5.0],
+            ..loan("01/01/2025", "31/12/2027", "1000", 5.0, "100000")
+        };
+        let summaries = compare_strategies(&params, &compare_args).unwrap();
+
+        let capped = &summaries[1];
+        assert!(capped.early_repayment_charges.is_zero());
+        assert_eq!(capped.net_saving, capped.interest_saved);
+
+        // Overpaying 20000 a year goes over the allowance, by 10000 in the first year alone
+        let uncapped = &summaries[2];
+        assert!(uncapped.early_repayment_charges.is_positive());
+        assert_eq!(
+            uncapped.net_saving,
+            uncapped.interest_saved - uncapped.early_repayment_charges
+        );
+
+        let table = render_comparison(&summaries, OutputFormat::Table).unwrap();
+        assert!(table.contains("After early repayment charges the best net saving is"));
+    }
+
+    #[test]
+    fn test_flat_inflation_deflator() {
+        let deflator = Deflator::Flat {
+            base: date("01/01/2025"),
+            rate: 2.0,
+        };
+        assert_eq!(deflator.price_level(&date("01/01/2025")), 1.0);
+        assert_eq!(
+            deflator.to_real(money("1020"), &date("01/01/2026"), RoundingMode::HalfEven),
+            money("1000.01")
+        );
+    }
+
+    #[test]
+    fn test_cpi_series_deflator() {
+        let series = parse_cpi_series(
+            "date,index\n01/01/2025,100\n# rebased\n01/01/2026,105\n",
+            false,
+        )
+        .unwrap();
+        assert_eq!(series.len(), 2);
+        let deflator = Deflator::Index {
+            base: date("01/06/2025"),
+            series,
+        };
+
+        assert_eq!(deflator.price_level(&date("01/03/2026")), 1.05);
+        // Before the series the first index applies, and after it the last one holds
+        assert_eq!(deflator.price_level(&date("01/01/2024")), 1.0);
+        assert_eq!(deflator.price_level(&date("01/01/2040")), 1.05);
+        assert_eq!(
+            deflator.to_real(money("105"), &date("01/03/2026"), RoundingMode::HalfEven),
+            money("100")
+        );
+
+        assert!(parse_cpi_series("01/01/2025,-1\n", false).is_err());
+        assert!(parse_cpi_series("01/01/2025\n", false).is_err());
+    }
+
+    #[test]
+    fn test_deflate_schedule() {
+        let deflator = Deflator::Index {
+            base: date("01/01/2025"),
+            series: vec![(date("01/01/2025"), 100.0), (date("01/07/2025"), 200.0)],
+        };
+        let mut schedule = calculate_interest_data_for_period(&loan(
+            "01/01/2025",
+            "31/12/2025",
+            "1000",
+            0.0,
+            "12000",
+        ));
+        assert!(schedule.real_totals().is_none());
+
+        deflate_schedule(&mut schedule, &deflator, RoundingMode::HalfEven);
+        // Prices double halfway through, halving the real value of the later payments
+        assert_eq!(schedule.rows[6].real.unwrap().paid, money("500"));
+        assert_eq!(schedule.real_totals(), Some((money("9000"), Money::ZERO)));
+
+        let table = render_schedule(&schedule, OutputFormat::Table).unwrap();
+        assert!(table.contains("Real interest"));
+        assert!(
+            table.contains("In today's money that is 9000.00 paid, of which 0.00 was interest")
+        );
+        let csv = render_schedule(&schedule, OutputFormat::Csv).unwrap();
+        assert!(csv.starts_with(
+            "date,opening_balance,interest,payment,overpayment,closing_balance,real_interest,real_paid,real_closing_balance\n"
+        ));
+        let json = render_schedule(&schedule, OutputFormat::Json).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed["real_total_paid"], 9000.0);
+        assert_eq!(parsed["rows"][6]["real"]["paid"], 500.0);
+
+        // An empty schedule has no real columns
+        let empty = AmortizationSchedule::default();
+        assert!(empty.real_totals().is_none());
+        let table = render_schedule(&empty, OutputFormat::Table).unwrap();
+        assert!(!table.contains("Real interest"));
+        assert!(!table.contains("In today's money"));
+        let csv = render_schedule(&empty, OutputFormat::Csv).unwrap();
+        assert!(!csv.contains("real_paid"));
+    }
+
+    #[test]
+    fn test_deflate_savings() {
+        let deflator = Deflator::Flat {
+            base: date("01/01/2025"),
+            rate: 0.0,
+        };
+        let mut projection = project(
+            loan("01/01/2025", "31/12/2025", "0", 12.0, "10000"),
+            &savings_args(SavingsCompounding::Monthly),
+        );
+        deflate_savings(&mut projection, &deflator, RoundingMode::HalfEven);
+
+        // Without inflation the real figures match the nominal ones
+        assert_eq!(
+            projection.real_totals(),
+            Some((projection.total_interest(), projection.closing_balance()))
+        );
+        let table = render_savings(&projection, OutputFormat::Table).unwrap();
+        assert!(table.contains("In today's money the balance is"));
+    }
+
+    #[test]
+    fn test_splitmix_is_reproducible() {
+        let mut rng = SplitMix64::new(0);
+        assert_eq!(rng.next_u64(), 0xE220_A839_7B1D_CDAF);
+
+        let mut first = SplitMix64::new(42);
+        let mut second = SplitMix64::new(42);
+        for _ in 0..100 {
+            let value = first.next_f64();
+            assert!((0.0..1.0).contains(&value));
+            assert_eq!(value, second.next_f64());
+        }
+    }
+
+    #[test]
+    fn test_percentiles_nearest_rank() {
+        let bands = percentiles((1..=10).rev().collect::<Vec<i32>>());
+        assert_eq!((bands.p10, bands.p50, bands.p90), (1, 5, 9));
+
+        let single = percentiles(vec![7]);
+        assert_eq!((single.p10, single.p50, single.p90), (7, 7, 7));
+    }
+
+    fn montecarlo_args(seed: u64, remortgage_dates: &[&str], shocks: Vec<f64>) -> MonteCarlo {
+        MonteCarlo {
+            paths: 25,
+            seed,
+            volatility: 1.0,
+            remortgage_dates: remortgage_dates.iter().map(|d| d.to_string()).collect(),
+            shocks,
+        }
+    }
+
+    #[test]
+    fn test_montecarlo_fixed_shock_matches_rate_change() {
+        let params = loan("01/01/2025", "31/12/2026", "1000", 5.0, "20000");
+        let args = montecarlo_args(1, &["15/12/2025"], vec![1.0]);
+        let summary = simulate_paths(&params, &args, false).unwrap();
+
+        // Every path is the same, a one point rise from January 2026
+        let expected = calculate_interest_data_for_period(&LoanParameters {
+            rate_changes: vec![(date("01/01/2026"), 6.0)],
+            ..params
+        });
+        let total_interest = summary.total_interest.unwrap();
+        assert_eq!(total_interest.p10, expected.total_interest());
+        assert_eq!(total_interest.p90, expected.total_interest());
+        assert_eq!(summary.payoff_date.p50, expected.payoff_date());
+        assert_eq!(summary.uncleared, 0);
+    }
+
+    #[test]
+    fn test_montecarlo_is_deterministic_for_a_seed() {
+        let params = loan("01/01/2025", "31/12/2029", "0", 5.0, "50000");
+        let params = LoanParameters {
+            monthly_payment: annuity_payment(money("50000"), 5.0, 60, RoundingMode::HalfEven),
+            term_end: Some(date("31/12/2029")),
+            ..params
+        };
+
+        let first = simulate_paths(&params, &montecarlo_args(7, &[], vec![]), false).unwrap();
+        let again = simulate_paths(&params, &montecarlo_args(7, &[], vec![]), false).unwrap();
+        let other = simulate_paths(&params, &montecarlo_args(8, &[], vec![]), false).unwrap();
+
+        assert_eq!(first.total_interest, again.total_interest);
+        assert_ne!(first.total_interest, other.total_interest);
+        let total_interest = first.total_interest.unwrap();
+        assert!(total_interest.p10 <= total_interest.p50);
+        assert!(total_interest.p50 <= total_interest.p90);
+        // The payment is recalculated at each change, so every path clears by the end of the term
+        assert_eq!(first.uncleared, 0);
+    }
+
+    #[test]
+    fn test_montecarlo_payoff_dates_spread_across_paths() {
+        // The payment is kept on every path, so the payoff date moves with the rates, whether
+        // or not there is an end date
+        for (open_ended, end_date) in [(true, "31/12/2124"), (false, "31/12/2070")] {
+            let params = LoanParameters {
+                open_ended,
+                ..loan("01/01/2025", end_date, "1500", 4.19, "250000")
+            };
+            let args = MonteCarlo {
+                paths: 200,
+                ..montecarlo_args(7, &[], vec![])
+            };
+            let summary = simulate_paths(&params, &args, false).unwrap();
+
+            let (p10, p50) = (
+                summary.payoff_date.p10.unwrap(),
+                summary.payoff_date.p50.unwrap(),
+            );
+            assert!(p10 < p50, "{:?}", summary.payoff_date);
+            assert!(
+                summary.payoff_date.p90.is_none_or(|p90| p50 < p90),
+                "{:?}",
+                summary.payoff_date
+            );
+            let total_interest = summary.total_interest.unwrap();
+            assert!(total_interest.p10 < total_interest.p90);
+        }
+    }
+
+    #[test]
+    fn test_montecarlo_stops_paths_that_stop_falling() {
+        let params = LoanParameters {
+            open_ended: true,
+            ..loan("01/01/2025", "31/12/2124", "100", 30.0, "250000")
+        };
+        let summary = simulate_paths(&params, &montecarlo_args(7, &[], vec![]), false).unwrap();
+
+        // Each path stops after two months rather than growing for a hundred years, leaving no
+        // total interest to report
+        assert_eq!(summary.uncleared, 25);
+        assert_eq!(summary.total_interest, None);
+        let table = render_summary(&summary, OutputFormat::Table).unwrap();
+        assert!(table.contains("Total interest               -             -             -"));
+    }
+
+    #[test]
+    fn test_montecarlo_reports_uncleared_paths() {
+        let params = LoanParameters {
+            open_ended: true,
+            ..loan("01/01/2025", "31/12/2030", "500", 2.0, "20000")
+        };
+        // Half the paths jump to a rate the payment can't keep up with
+        let args = MonteCarlo {
+            paths: 40,
+            ..montecarlo_args(3, &["01/01/2026"], vec![0.0, 40.0])
+        };
+        let summary = simulate_paths(&params, &args, false).unwrap();
+
+        assert!(summary.uncleared > 0 && summary.uncleared < 40);
+        assert_eq!(summary.payoff_date.p90, None);
+        assert!(summary.payoff_date.p10.is_some());
+
+        let table = render_summary(&summary, OutputFormat::Table).unwrap();
+        assert!(table.contains("not cleared"));
+        assert!(table.contains(&format!(
+            "{} of 40 paths don't clear the balance by 2030-12-31, and are left out of the total interest",
+            summary.uncleared
+        )));
+        let csv = render_summary(&summary, OutputFormat::Csv).unwrap();
+        assert!(csv.starts_with("measure,p10,p50,p90\n"));
+        let json = render_summary(&summary, OutputFormat::Json).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert!(parsed["payoff_date"]["p90"].is_null());
+
+        // Only the paths that keep the 2% rate clear the balance, so the truncated interest on
+        // the others doesn't pull the bands down
+        let cleared = calculate_interest_data_for_period(&params).total_interest();
+        assert_eq!(
+            summary.total_interest,
+            Some(Percentiles {
+                p10: cleared,
+                p50: cleared,
+                p90: cleared
+            })
+        );
+    }
+
+    #[test]
+    fn test_montecarlo_rejects_rate_changes() {
+        let params = LoanParameters {
+            rate_changes: vec![(date("01/06/2025"), 6.0)],
+            ..loan("01/01/2025", "31/12/2026", "1000", 5.0, "20000")
+        };
+        assert!(simulate_paths(&params, &montecarlo_args(0, &[], vec![]), false).is_err());
+        let params = loan("01/01/2025", "31/12/2026", "1000", 5.0, "20000");
+        assert!(
+            simulate_paths(&params, &montecarlo_args(0, &["01/01/2025"], vec![]), false).is_err()
+        );
+    }
+
+    fn offer(name: &str, rate: f64, term: u8) -> Offer {
+        Offer {
+            name: name.to_string(),
+            rate,
+            term,
+            product_fee: Money::ZERO,
+            valuation_fee: Money::ZERO,
+            cashback: Money::ZERO,
+            fee_added_to_loan: false,
+        }
+    }
+
+    #[test]
+    fn test_parse_offers_toml_and_csv() {
+        let toml = r#"
+[[offer]]
+name = "Two year fix"
+rate = 4.19
+term = 2
+product_fee = 999
+
+[[offer]]
+name = "Five year cashback"
+rate = 4.49
+term_years = 5
+cashback = 500.50
+"#;
+        let csv = "# Offers from the broker\n\
+                   name,rate,term,product_fee,valuation_fee,cashback\n\
+                   Two year fix,4.19%,2,999,,\n\
+                   Five year cashback,4.49,5,,,500.50\n";
+        let expected = vec![
+            Offer {
+                product_fee: money("999"),
+                ..offer("Two year fix", 4.19, 2)
+            },
+            Offer {
+                cashback: money("500.50"),
+                ..offer("Five year cashback", 4.49, 5)
+            },
+        ];
+
+        assert_eq!(
+            parse_offers(Path::new("offers.toml"), toml).unwrap(),
+            expected
+        );
+        assert_eq!(
+            parse_offers(Path::new("offers.CSV"), csv).unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_parse_offers_rejects_invalid_files() {
+        let csv = Path::new("offers.csv");
+        assert!(parse_offers(csv, "name,rate\nFix,4.19\n").is_err());
+        assert!(parse_offers(csv, "name,rate,term\nFix,4.19\n").is_err());
+        assert!(parse_offers(csv, "name,rate,term\nFix,abc,2\n").is_err());
+        assert!(parse_offers(csv, "name,rate,term\nFix,4.19,0\n").is_err());
+        assert!(parse_offers(csv, "name,rate,term,cashback\nFix,4.19,2,-5\n").is_err());
+        assert!(parse_offers(csv, "name,rate,term\n").is_err());
+        assert!(parse_offers(Path::new("offers.toml"), "[[offer]]\nname = \"Fix\"\n").is_err());
+    }
+
+    #[test]
+    fn test_annual_percentage_rate_of_an_annuity() {
+        // Level payments on a 6% loan with no fees give the effective annual rate
+        let payments = vec![annuity_payment(money("100000"), 6.0, 60, RoundingMode::HalfEven); 60];
+        let aprc = annual_percentage_rate(money("100000"), &payments);
+        assert!((aprc - 6.1678).abs() < 0.001, "{aprc}");
+
+        // Fees paid out of the advance push it up
+        assert!(annual_percentage_rate(money("99000"), &payments) > aprc + 0.3);
+    }
+
+    #[test]
+    fn test_compare_offers_costs_fees_and_cashback() {
+        let params = loan("01/01/2025", "31/12/2049", "0", 4.5, "200000");
+        let offers = vec![
+            Offer {
+                product_fee: money("999"),
+                valuation_fee: money("250"),
+                ..offer("Fee", 4.19, 2)
+            },
+            Offer {
+                cashback: money("500"),
+                ..offer("Cashback", 4.19, 2)
+            },
+            offer("Plain", 4.19, 2),
+        ];
+        let summaries = compare_offers(&params, &offers).unwrap();
+        let names = summaries
+            .iter()
+            .map(|summary| summary.name.as_str())
+            .collect::<Vec<&str>>();
+        assert_eq!(names, ["Cashback", "Plain", "Fee"]);
+
+        // The same rate and term cost the same interest, with a payment that clears the loan by
+        // the end date
+        let payment = annuity_payment(money("200000"), 4.19, 300, RoundingMode::HalfEven);
+        for summary in &summaries {
+            assert_eq!(summary.monthly_payment, payment);
+            assert_eq!(summary.interest, summaries[0].interest);
+            assert_eq!(summary.total_cost, summary.interest + summary.net_fees);
+            assert!(summary.balance_at_end.is_positive());
+        }
+        assert_eq!(summaries[0].net_fees, money("-500"));
+        assert_eq!(summaries[2].net_fees, money("1249"));
+        assert_eq!(
+            summaries[2].cost_per_year(),
+            summaries[2].total_cost.mul_f64(0.5)
+        );
+
+        // Two years of payments from 1 January 2025 finish on 1 December 2026
+        let schedule = calculate_interest_data_for_period(&LoanParameters {
+            monthly_payment: payment,
+            interest_rate: 4.19,
+            end_date: date("31/12/2026"),
+            ..params.clone()
+        });
+        assert_eq!(summaries[1].interest, schedule.total_interest());
+        assert_eq!(summaries[1].balance_at_end, schedule.closing_balance());
+    }
+
+    #[test]
+    fn test_compare_offers_with_fee_added_to_loan() {
+        let params = loan("01/01/2025", "31/12/2049", "0", 4.5, "200000");
+        let added = Offer {
+            product_fee: money("1000"),
+            fee_added_to_loan: true,
+            ..offer("Added", 4.0, 5)
+        };
+        let upfront = Offer {
+            fee_added_to_loan: false,
+            ..added.clone()
+        };
+        let summaries = compare_offers(&params, &[added, upfront]).unwrap();
+        let added = summaries.iter().find(|s| s.name == "Added").unwrap();
+
+        // Interest accrues on the fee as well, but it isn't paid out of the advance
+        assert!(summaries.iter().all(|s| s.net_fees == money("1000")));
+        assert_eq!(
+            added.monthly_payment,
+            annuity_payment(money("201000"), 4.0, 300, RoundingMode::HalfEven)
+        );
+        assert!(summaries.iter().all(|s| s.aprc > 4.0));
+    }
+
+    #[test]
+    fn test_compare_offers_needs_a_payment_when_open_ended() {
+        let open_ended = LoanParameters {
+            open_ended: true,
+            ..loan("01/01/2025", "31/12/2049", "0", 4.5, "200000")
+        };
+        assert!(compare_offers(&open_ended, &[offer("Fix", 4.19, 2)]).is_err());
+
+        let summaries = compare_offers(
+            &LoanParameters {
+                monthly_payment: money("1500"),
+                ..open_ended
+            },
+            &[offer("Fix", 4.19, 2)],
+        )
+        .unwrap();
+        assert_eq!(summaries[0].monthly_payment, money("1500"));
+    }
+
+    #[test]
+    fn test_render_offers() {
+        let params = loan("01/01/2025", "31/12/2049", "0", 4.5, "200000");
+        let summaries = compare_offers(
+            &params,
+            &[offer("Two year", 4.19, 2), offer("Five year", 4.49, 5)],
+        )
+        .unwrap();
+
+        let table = render_offers(&summaries, OutputFormat::Table).unwrap();
+        assert!(table.starts_with("Rank Offer"));
+        assert!(table.contains("1    Two year"));
+        let csv = render_offers(&summaries, OutputFormat::Csv).unwrap();
+        assert!(csv.starts_with(
+            "rank,offer,rate,term,monthly_payment,interest,net_fees,total_cost,cost_per_year,balance_at_end,aprc\n1,Two year,4.19,2,"
+        ));
+        let json = render_offers(&summaries, OutputFormat::Json).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed[1]["name"], "Five year");
+        assert_eq!(parsed[1]["term"], 5);
+    }
+
+    fn debt(name: &str, balance: &str, rate: f64, minimum: &str) -> Debt {
+        Debt {
+            name: name.to_string(),
+            balance: money(balance),
+            rate,
+            minimum: money(minimum),
+        }
+    }
+
+    fn debt_params(budget: &str) -> DebtParameters {
+        DebtParameters {
+            start_date: date("01/01/2025"),
+            end_date: date("31/12/2034"),
+            budget: money(budget),
+            rounding: RoundingMode::HalfEven,
+            compounding: Compounding::Daily,
+            day_count: DayCount::ActualActual,
+        }
+    }
+
+    fn sample_debts() -> Vec<Debt> {
+        vec![
+            debt("Credit card", "4800", 22.9, "120"),
+            debt("Car finance", "8500", 6.9, "210"),
+            debt("Store card", "650", 29.9, "25"),
+            debt("Personal loan", "3000", 9.5, "90"),
+        ]
+    }
+
+    #[test]
+    fn test_parse_debts_toml_and_csv() {
+        let toml = r#"
+[[debt]]
+name = "Credit card"
+balance = 4800
+rate = 22.9
+minimum = 120
+
+[[debt]]
+name = "Car finance"
+balance = 8500.50
+rate = 6.9
+minimum_payment = 210
+"#;
+        let csv = "name,rate,balance,minimum_payment\n\
+                   Credit card,22.9%,4800,120\n\
+                   \n\
+                   Car finance,6.9,8500.50,210\n";
+        let expected = vec![
+            debt("Credit card", "4800", 22.9, "120"),
+            debt("Car finance", "8500.50", 6.9, "210"),
+        ];
+
+        assert_eq!(
+            parse_debts(Path::new("debts.toml"), toml).unwrap(),
+            expected
+        );
+        assert_eq!(parse_debts(Path::new("debts.csv"), csv).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_parse_debts_rejects_invalid_files() {
+        let csv = Path::new("debts.csv");
+        assert!(parse_debts(csv, "name,balance,rate\nCard,100,20\n").is_err());
+        assert!(parse_debts(csv, "name,balance,rate,minimum\nCard,100,20\n").is_err());
+        assert!(parse_debts(csv, "name,balance,rate,minimum\nCard,0,20,10\n").is_err());
+        assert!(parse_debts(csv, "name,balance,rate,minimum\nCard,100,-1,10\n").is_err());
+        assert!(parse_debts(csv, "name,balance,rate,minimum\nCard,100,20,-10\n").is_err());
+        assert!(
+            parse_debts(
+                csv,
+                "name,balance,rate,minimum\nCard,100,20,10\ncard,200,10,10\n"
+            )
+            .is_err()
+        );
+        assert!(parse_debts(csv, "name,balance,rate,minimum\n").is_err());
+    }
+
+    #[test]
+    fn test_target_order() {
+        let debts = sample_debts();
+        assert_eq!(
+            target_order(&debts, Strategy::Snowball, &[]).unwrap(),
+            [2, 3, 0, 1]
+        );
+        assert_eq!(
+            target_order(&debts, Strategy::Avalanche, &[]).unwrap(),
+            [2, 0, 3, 1]
+        );
+        assert_eq!(
+            target_order(&debts, Strategy::Custom, &[]).unwrap(),
+            [0, 1, 2, 3]
+        );
+        let order = ["car finance".to_string(), " Personal loan".to_string()];
+        assert_eq!(
+            target_order(&debts, Strategy::Custom, &order).unwrap(),
+            [1, 3, 0, 2]
+        );
+
+        assert!(target_order(&debts, Strategy::Custom, &["Mortgage".to_string()]).is_err());
+        let repeated = [
+            "Car finance".to_string(),
+            "Store card".to_string(),
+            "Car finance".to_string(),
+        ];
+        assert!(target_order(&debts, Strategy::Custom, &repeated).is_err());
+    }
+
+    #[test]
+    fn test_single_debt_matches_loan_schedule() {
+        let params = debt_params("250");
+        let debts = [debt("Loan", "10000", 7.5, "100")];
+        let outcome = simulate_plan(&params, &debts, &[0]);
+
+        let schedule = calculate_interest_data_for_period(&loan(
+            "01/01/2025",
+            "31/12/2034",
+            "250",
+            7.5,
+            "10000",
+        ));
+        assert_eq!(outcome[0].interest, schedule.total_interest());
+        assert_eq!(outcome[0].payoff_date, schedule.payoff_date());
+        assert_eq!(outcome[0].payments, schedule.rows.len());
+    }
+
+    #[test]
+    fn test_interest_free_debts_roll_over() {
+        let params = debt_params("300");
+        let debts = [
+            debt("First", "500", 0.0, "100"),
+            debt("Second", "1000", 0.0, "100"),
+        ];
+        let outcome = simulate_plan(&params, &debts, &[0, 1]);
+
+        // The first gets 200 a month while the second gets its minimum. Once the first is
+        // cleared in March, the second gets all 300.
+        assert_eq!(outcome[0].payoff_date, Some(date("01/03/2025")));
+        assert_eq!(outcome[0].payments, 3);
+        assert_eq!(outcome[1].payoff_date, Some(date("01/05/2025")));
+        assert_eq!(outcome[1].payments, 5);
+        assert!(outcome.iter().all(|debt| debt.interest.is_zero()));
+    }
+
+    #[test]
+    fn test_plan_debts_compares_orders() {
+        let plans = plan_debts(
+            &debt_params("700"),
+            &sample_debts(),
+            &["Car finance".to_string()],
+        )
+        .unwrap();
+        let strategies = plans
+            .iter()
+            .map(|plan| plan.strategy)
+            .collect::<Vec<Strategy>>();
+        assert_eq!(
+            strategies,
+            [Strategy::Snowball, Strategy::Avalanche, Strategy::Custom]
+        );
+        let [snowball, avalanche, custom] = &plans[..] else {
+            panic!("Expected three plans");
+        };
+
+        // Paying the highest rate first costs the least, and everything is cleared by the end
+        assert!(avalanche.total_interest() < snowball.total_interest());
+        assert!(avalanche.total_interest() < custom.total_interest());
+        for plan in &plans {
+            assert_eq!(
+                plan.debt_free_date(),
+                plan.debts.iter().filter_map(|debt| debt.payoff_date).max()
+            );
+            assert!(plan.debts.iter().all(|debt| debt.interest.is_positive()));
+        }
+        assert_eq!(custom.debts[0].name, "Car finance");
+        // The targeted debt is cleared first
+        assert!(
+            custom
+                .debts
+                .iter()
+                .skip(1)
+                .all(|debt| debt.payoff_date > custom.debts[0].payoff_date)
+        );
+    }
+
+    #[test]
+    fn test_plan_debts_needs_the_minimum_payments() {
+        assert!(plan_debts(&debt_params("444.99"), &sample_debts(), &[]).is_err());
+        assert!(plan_debts(&debt_params("445"), &sample_debts(), &[]).is_ok());
+    }
+
+    #[test]
+    fn test_plan_debts_needs_payments_that_cover_the_interest() {
+        let debts = [
+            debt("Card", "5000", 29.9, "10"),
+            debt("Loan", "3000", 7.0, "100"),
+        ];
+        assert_eq!(
+            plan_debts(&debt_params("110"), &debts, &[])
+                .unwrap_err()
+                .to_string(),
+            "The monthly budget of 110.00 doesn't cover the monthly interest of about 142.08, so the debts are never paid off"
+        );
+        assert_eq!(
+            plan_debts(&debt_params("500"), &debts, &[])
+                .unwrap_err()
+                .to_string(),
+            "The minimum payment of 10.00 on 'Card' doesn't cover its monthly interest of about 124.58"
+        );
+
+        let debts = [
+            debt("Card", "5000", 29.9, "125"),
+            debt("Loan", "3000", 7.0, "100"),
+        ];
+        let plans = plan_debts(&debt_params("500"), &debts, &[]).unwrap();
+        assert!(plans.iter().all(|plan| plan.debt_free_date().is_some()));
+    }
+
+    #[test]
+    fn test_render_plans() {
+        let mut params = debt_params("700");
+        let plans = plan_debts(&params, &sample_debts(), &[]).unwrap();
+        let table = render_plans(&plans, &params.end_date, OutputFormat::Table).unwrap();
+        assert!(table.starts_with("Snowball: debt free on "));
+        assert!(table.contains("Avalanche pays the least interest"));
+        let csv = render_plans(&plans, &params.end_date, OutputFormat::Csv).unwrap();
+        assert!(csv.starts_with(
+            "strategy,debt,balance,rate,minimum,payoff_date,payments,interest\nsnowball,Store card,650.00,29.90,25.00,"
+        ));
+        assert_eq!(csv.lines().count(), 13);
+        let json = render_plans(&plans, &params.end_date, OutputFormat::Json).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed[1]["strategy"], "avalanche");
+        assert_eq!(parsed[1]["debts"][0]["name"], "Store card");
+
+        // Debts not cleared by the end date are reported as such
+        params.budget = money("100");
+        params.end_date = date("31/12/2025");
+        let plans = plan_debts(&params, &[debt("Card", "5000", 20.0, "100")], &[]).unwrap();
+        assert_eq!(plans[0].debt_free_date(), None);
+        let table = render_plans(&plans, &params.end_date, OutputFormat::Table).unwrap();
+        assert!(table.contains("not debt free by 2025-12-31"));
+        assert!(table.contains("not cleared"));
+        assert!(table.contains("Every order pays the same interest"));
+        let json = render_plans(&plans, &params.end_date, OutputFormat::Json).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert!(parsed[0]["debts"][0]["payoff_date"].is_null());
+    }
+
+    #[test]
+    fn test_parse_scenario_toml_and_json() {
+        let toml = r#"
+principal = 200000
+interest_rate = 4.19
+repayment = "1100.50"
+annual_downpayment = 5000
+end_date = "31/12/2050"
+rate_changes = ["01/04/2028=5.49"]
+anniversary = "15/06"
+erc = [5, 4, 3]
+rate_schedule = "rates.csv"
+cpi = "/data/cpi.csv"
+day_count = "act/365"
+convention = "monthly-rest"
+format = "csv"
+"#;
+        let scenario = parse_scenario(Path::new("scenarios/remortgage.toml"), toml).unwrap();
+        assert_eq!(scenario.principal, Some(money("200000")));
+        assert_eq!(scenario.interest_rate, Some(4.19));
+        assert_eq!(scenario.repayment, Some(money("1100.50")));
+        assert_eq!(scenario.max_repayment_pct, None);
+        assert_eq!(scenario.annual_downpayment, Some(money("5000")));
+        assert_eq!(
+            scenario.rate_changes,
+            vec!["01/04/2028=5.49".parse().unwrap()]
+        );
+        assert_eq!(
+            scenario.anniversary,
+            Some(Anniversary { day: 15, month: 6 })
+        );
+        assert_eq!(scenario.early_repayment_charges, vec![5.0, 4.0, 3.0]);
+        assert_eq!(scenario.day_count, DayCount::Actual365);
+        assert_eq!(scenario.convention, Compounding::Monthly);
+        assert_eq!(scenario.format, OutputFormat::Csv);
+        assert_eq!(scenario.rounding, RoundingMode::HalfEven);
+        // Relative files are found next to the scenario
+        assert_eq!(
+            scenario.rate_schedule.as_deref(),
+            Some(Path::new("scenarios/rates.csv"))
+        );
+        assert_eq!(scenario.cpi.as_deref(), Some(Path::new("/data/cpi.csv")));
+
+        let json = r#"{"principal": 150000, "interest_rate": 3.5, "max_repayment_pct": 10, "rounding": "half-up"}"#;
+        let scenario = parse_scenario(Path::new("loan.JSON"), json).unwrap();
+        assert_eq!(scenario.principal, Some(money("150000")));
+        assert_eq!(scenario.max_repayment_pct, Some(10));
+        assert_eq!(scenario.rounding, RoundingMode::HalfUp);
+        assert_eq!(scenario.end_date, None);
+    }
+
+    #[test]
+    fn test_parse_scenario_rejects_invalid_files() {
+        let toml = Path::new("scenario.toml");
+        assert!(parse_scenario(toml, "principle = 1000\n").is_err());
+        assert!(parse_scenario(toml, "anniversary = \"31/02\"\n").is_err());
+        assert!(parse_scenario(toml, "rate_changes = [\"01/04/2028\"]\n").is_err());
+        assert!(parse_scenario(toml, "day_count = \"act/364\"\n").is_err());
+        assert!(parse_scenario(toml, "offset = 1000\noffset_schedule = \"offset.csv\"\n").is_err());
+        assert!(parse_scenario(toml, "inflation = 2\ncpi = \"cpi.csv\"\n").is_err());
+        assert!(parse_scenario(Path::new("scenario.json"), "principal = 1000").is_err());
+    }
+
+    #[test]
+    fn test_merge_scenario_with_command_line() {
+        let scenario = Interest {
+            max_repayment_pct: None,
+            annual_downpayment: Some(money("5000")),
+            early_repayment_charges: vec![5.0, 4.0],
+            savings_rate: 3.0,
+            day_count: DayCount::Actual365,
+            inflation: Some(2.5),
+            ..rate_change_args(&["01/04/2028=5.49"])
+        };
+        // Only what was passed on the command line
+        let cli = Interest {
+            interest_rate: Some(3.9),
+            max_repayment_pct: Some(15),
+            format: OutputFormat::Json,
+            ..Interest::default()
+        };
+        let merged = merge(scenario, cli);
+
+        assert_eq!(merged.principal, Some(Money::from_major(100000)));
+        assert_eq!(merged.interest_rate, Some(3.9));
+        assert_eq!(merged.repayment, Some(Money::from_major(1000)));
+        assert_eq!(merged.end_date.as_deref(), Some("31/12/2030"));
+        assert_eq!(merged.rate_changes.len(), 1);
+        assert_eq!(merged.early_repayment_charges, vec![5.0, 4.0]);
+        // -m on the command line sets the allowance for the scenario's -a
+        assert_eq!(merged.max_repayment_pct, Some(15));
+        assert_eq!(merged.annual_downpayment, Some(money("5000")));
+        // Options with defaults keep the scenario's unless changed
+        assert_eq!(merged.format, OutputFormat::Json);
+        assert_eq!(merged.savings_rate, 3.0);
+        assert_eq!(merged.day_count, DayCount::Actual365);
+        assert_eq!(merged.inflation, Some(2.5));
+
+        let merged = merge(
+            rate_change_args(&["01/04/2028=5.49"]),
+            Interest {
+                rate_changes: vec!["01/01/2027=3".parse().unwrap()],
+                cpi: Some("cpi.csv".into()),
+                ..Interest::default()
+            },
+        );
+        assert_eq!(merged.rate_changes, vec!["01/01/2027=3".parse().unwrap()]);
+        assert_eq!(merged.max_repayment_pct, Some(10));
+        assert_eq!(merged.cpi.as_deref(), Some(Path::new("cpi.csv")));
+    }
+
+    #[test]
+    fn test_render_scenario_round_trips() {
+        let args = Interest {
+            anniversary: Some(Anniversary { day: 5, month: 9 }),
+            early_repayment_charges: vec![3.0, 2.5],
+            convention: Compounding::Annual,
+            day_count: DayCount::Thirty360,
+            rate_schedule: Some("/data/rates.csv".into()),
+            ..rate_change_args(&["01/04/2028=5.49", "01/04/2030=4.25"])
+        };
+
+        for path in ["saved.toml", "saved.json"] {
+            let path = Path::new(path);
+            let contents = render_scenario(path, &args).unwrap();
+            let saved = parse_scenario(path, &contents).unwrap();
+            assert_eq!(format!("{saved:?}"), format!("{args:?}"));
+        }
+        let toml = render_scenario(Path::new("saved.toml"), &args).unwrap();
+        assert!(toml.contains("anniversary = \"05/09\""));
+        assert!(toml.contains("day_count = \"30/360\""));
+    }
 }
@@ -1,4 +1,5 @@
 mod conversions;
+mod csv_utils;
 mod currency;
 mod dateinfo;
 mod ddg;
@@ -20,7 +21,7 @@ fn main() -> Result<()> {
     let da_answer = match cli.command {
         Commands::Convert(args) => conversions::perform_conversion(args),
         Commands::Dates(args) => dateinfo::handle_date_operations(args, verbose),
-        Commands::Interest(args) => interest::handle_interest_calculations(args, verbose),
+        Commands::Interest(args) => interest::handle_interest_calculations(*args, verbose),
         Commands::Currency(args) => currency::handle_currency_operations(args, verbose),
         Commands::Mileage(args) => mileage::handle_mileage_operations(args, verbose),
         Commands::DDG(args) => ddg::handle_ddg_operations(args, verbose),
@@ -63,15 +64,16 @@ mod tests {
     #[test]
     fn test_error_propagation() {
         use lifestuff_types::interest::Interest;
+        use lifestuff_types::money::Money;
 
         // Test that errors from handlers are properly propagated
         let invalid_interest = Interest {
-            principal: -1000.0, // Invalid negative principal
-            interest_rate: 5.0,
-            repayment: 500.0,
+            principal: Some(Money::from_major(-1000)), // Invalid negative principal
+            interest_rate: Some(5.0),
+            repayment: Some(Money::from_major(500)),
             max_repayment_pct: Some(10),
-            annual_downpayment: None,
-            end_date: "2024-01-01".to_string(),
+            end_date: Some("2024-01-01".to_string()),
+            ..Interest::default()
         };
 
         let result = interest::handle_interest_calculations(invalid_interest, false);
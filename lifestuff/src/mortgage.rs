@@ -1,4 +1,5 @@
 use anyhow::{Context, Result};
+use lifestuff_types::money::Money;
 use lifestuff_types::mortgage::{
     InterestPosting, InterestPostingArgs, InterestPostingRequest, MortgageCommand,
     MortgageOperation, MortgageSummary, PaymentArgs, PaymentRecord, PaymentRequest,
@@ -93,9 +94,9 @@ fn get_base_url(cli_endpoint: Option<String>) -> String {
 }
 
 /// Validate payment amount
-fn validate_payment_amount(amount: f64) -> Result<()> {
+fn validate_payment_amount(amount: Money) -> Result<()> {
     anyhow::ensure!(
-        amount > 0.0,
+        amount.is_positive(),
         "Payment amount must be positive, got: {}",
         amount
     );
@@ -109,7 +110,7 @@ fn validate_payment_args(args: &PaymentArgs) -> Result<()> {
     // Validate monthly_payment if provided
     if let Some(amount) = args.monthly_payment {
         anyhow::ensure!(
-            amount >= 0.0,
+            !amount.is_negative(),
             "Monthly payment must be non-negative, got: {}",
             amount
         );
@@ -118,7 +119,7 @@ fn validate_payment_args(args: &PaymentArgs) -> Result<()> {
     // Validate overpayment if provided
     if let Some(amount) = args.overpayment {
         anyhow::ensure!(
-            amount > 0.0,
+            amount.is_positive(),
             "Overpayment must be positive, got: {}",
             amount
         );
@@ -141,7 +142,7 @@ fn handle_payment(args: PaymentArgs, base_url: &str, verbose: bool) -> Result<()
         } else if let Some(monthly) = args.monthly_payment {
             println!("Monthly payment: {}", monthly);
         } else {
-            println!("Monthly payment: 0.0 (pure overpayment)");
+            println!("Monthly payment: 0.00 (pure overpayment)");
         }
         if let Some(overpayment) = args.overpayment {
             println!("Overpayment: {}", overpayment);
@@ -152,13 +153,13 @@ fn handle_payment(args: PaymentArgs, base_url: &str, verbose: bool) -> Result<()
     // Determine scheduled_payment based on flags:
     // - use_default: None (API uses mortgage's default monthly payment)
     // - monthly_payment specified: Some(value) (explicit override)
-    // - neither: Some(0.0) (pure overpayment, no monthly payment)
+    // - neither: Some(0) (pure overpayment, no monthly payment)
     let scheduled_payment = if args.use_default {
         None
     } else if let Some(amount) = args.monthly_payment {
         Some(amount)
     } else {
-        Some(0.0)
+        Some(Money::ZERO)
     };
 
     let request_body = PaymentRequest {
@@ -195,8 +196,8 @@ fn handle_payment(args: PaymentArgs, base_url: &str, verbose: bool) -> Result<()
     Ok(())
 }
 
-/// Handle getting mortgage status
-fn handle_status(base_url: &str, verbose: bool) -> Result<()> {
+/// Fetch the mortgage summary from the status endpoint
+fn get_status(base_url: &str, verbose: bool) -> Result<MortgageSummary> {
     let client = create_client(base_url)?;
     let url = format!("{}/mortgage/status", base_url);
 
@@ -218,7 +219,23 @@ fn handle_status(base_url: &str, verbose: bool) -> Result<()> {
         println!("Response body: {}", body);
     }
 
-    let status_data: MortgageSummary = parse_api_result(status, &body, "status")?;
+    parse_api_result(status, &body, "status")
+}
+
+/// Fetch the current mortgage summary, resolving the endpoint the same way as the mortgage
+/// commands
+pub fn fetch_mortgage_summary(endpoint: Option<String>, verbose: bool) -> Result<MortgageSummary> {
+    let normalized_url = http_utils::normalize_api_url(get_base_url(endpoint));
+    if verbose {
+        println!("Using mortgage API at: {}", normalized_url);
+    }
+
+    get_status(&normalized_url, verbose)
+}
+
+/// Handle getting mortgage status
+fn handle_status(base_url: &str, verbose: bool) -> Result<()> {
+    let status_data = get_status(base_url, verbose)?;
 
     println!("\n=== Mortgage Status ===");
     println!("Current Balance: ${:.2}", status_data.current_balance);
@@ -487,13 +504,13 @@ mod tests {
 
     #[test]
     fn test_validate_payment_amount_positive() {
-        let result = validate_payment_amount(1000.0);
+        let result = validate_payment_amount(Money::from_major(1000));
         assert!(result.is_ok(), "Should accept positive amounts");
     }
 
     #[test]
     fn test_validate_payment_amount_negative() {
-        let result = validate_payment_amount(-100.0);
+        let result = validate_payment_amount(Money::from_major(-100));
         assert!(result.is_err(), "Should reject negative amounts");
         let err_msg = result.unwrap_err().to_string();
         assert!(
@@ -508,7 +525,7 @@ mod tests {
 
     #[test]
     fn test_validate_payment_amount_zero() {
-        let result = validate_payment_amount(0.0);
+        let result = validate_payment_amount(Money::ZERO);
         assert!(result.is_err(), "Should reject zero amounts");
         let err_msg = result.unwrap_err().to_string();
         assert!(
@@ -519,13 +536,13 @@ mod tests {
 
     #[test]
     fn test_validate_payment_amount_small_positive() {
-        let result = validate_payment_amount(0.01);
+        let result = validate_payment_amount(Money::from_minor(1));
         assert!(result.is_ok(), "Should accept small positive amounts");
     }
 
     #[test]
     fn test_validate_payment_amount_large() {
-        let result = validate_payment_amount(1_000_000.0);
+        let result = validate_payment_amount(Money::from_major(1_000_000));
         assert!(result.is_ok(), "Should accept large amounts");
     }
 
@@ -627,7 +644,7 @@ mod tests {
     fn test_payment_operation_with_negative_amount() {
         let args = PaymentArgs {
             use_default: false,
-            monthly_payment: Some(-500.0),
+            monthly_payment: Some(Money::from_major(-500)),
             overpayment: None,
             date: "01/01/2024".to_string(),
             note: None,
@@ -672,7 +689,7 @@ mod tests {
     fn test_payment_operation_with_valid_monthly_only() {
         let args = PaymentArgs {
             use_default: false,
-            monthly_payment: Some(500.0),
+            monthly_payment: Some(Money::from_major(500)),
             overpayment: None,
             date: "01/01/2024".to_string(),
             note: None,
@@ -686,7 +703,7 @@ mod tests {
         let args = PaymentArgs {
             use_default: false,
             monthly_payment: None,
-            overpayment: Some(1000.0),
+            overpayment: Some(Money::from_major(1000)),
             date: "01/01/2024".to_string(),
             note: None,
         };
@@ -698,8 +715,8 @@ mod tests {
     fn test_payment_operation_with_both_amounts() {
         let args = PaymentArgs {
             use_default: false,
-            monthly_payment: Some(500.0),
-            overpayment: Some(200.0),
+            monthly_payment: Some(Money::from_major(500)),
+            overpayment: Some(Money::from_major(200)),
             date: "01/01/2024".to_string(),
             note: None,
         };
@@ -711,8 +728,8 @@ mod tests {
     fn test_payment_operation_with_zero_monthly_and_overpayment() {
         let args = PaymentArgs {
             use_default: false,
-            monthly_payment: Some(0.0),
-            overpayment: Some(1000.0),
+            monthly_payment: Some(Money::ZERO),
+            overpayment: Some(Money::from_major(1000)),
             date: "01/01/2024".to_string(),
             note: None,
         };
@@ -728,7 +745,7 @@ mod tests {
         let args = PaymentArgs {
             use_default: true,
             monthly_payment: None,
-            overpayment: Some(500.0),
+            overpayment: Some(Money::from_major(500)),
             date: "01/01/2024".to_string(),
             note: None,
         };
@@ -742,7 +759,7 @@ mod tests {
     #[test]
     fn test_interest_posting_with_zero_amount() {
         let args = InterestPostingArgs {
-            amount: 0.0,
+            amount: Money::ZERO,
             date: "01/01/2024".to_string(),
             note: None,
         };